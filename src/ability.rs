@@ -1,11 +1,10 @@
 use crate::{
-    api_request,
     classes::Class,
     deserialize_with_default,
     item::ItemRarity,
     leaderboard::deserialize_to_vec,
     player::{Ability, AbilityNodeCoordinate, Icon},
-    Map, Set, WynnApiError, API_LOCATION,
+    Map, Set, WynnApiError, WynnClient,
 };
 use serde::{Deserialize, Serialize};
 
@@ -81,20 +80,24 @@ pub struct AspectTier {
     pub description: Vec<String>,
 }
 
+impl WynnClient {
+    pub async fn abilitiy_map(&self, class: Class) -> Result<AbilityMap, WynnApiError> {
+        self.api_request(&format!("/ability/map/{}", class.main_class()))
+            .await
+    }
+
+    pub async fn abilitiy_tree(&self, class: Class) -> Result<AbilityTree, WynnApiError> {
+        self.api_request(&format!("/ability/tree/{}", class.main_class()))
+            .await
+    }
+}
+
 pub async fn abilitiy_map(class: Class) -> Result<AbilityMap, WynnApiError> {
-    api_request(&format!(
-        "{API_LOCATION}/ability/map/{}",
-        class.main_class()
-    ))
-    .await
+    WynnClient::global().abilitiy_map(class).await
 }
 
 pub async fn abilitiy_tree(class: Class) -> Result<AbilityTree, WynnApiError> {
-    api_request(&format!(
-        "{API_LOCATION}/ability/tree/{}",
-        class.main_class()
-    ))
-    .await
+    WynnClient::global().abilitiy_tree(class).await
 }
 
 #[cfg(test)]
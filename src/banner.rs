@@ -0,0 +1,652 @@
+//! The colours and patterns of guild banners, with the `svg` feature banners can be rendered to an svg,
+//! and with the `png` feature to a png.
+
+use crate::deserialize_unknown;
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+
+#[cfg(feature = "svg")]
+use crate::{guild::Banner, svg::Num};
+
+/// The 16 dye colours of minecraft.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum BannerColour {
+    White,
+    Orange,
+    Magenta,
+    LightBlue,
+    Yellow,
+    Lime,
+    Pink,
+    Gray,
+    /// called `SILVER` before minecraft 1.13
+    #[serde(alias = "SILVER")]
+    LightGray,
+    Cyan,
+    Purple,
+    Blue,
+    Brown,
+    Green,
+    Red,
+    Black,
+    /// a value this crate doesn't know about, only decoded in [lenient](crate::with_lenient) mode
+    #[serde(untagged, deserialize_with = "deserialize_unknown")]
+    Unknown(String),
+}
+
+impl BannerColour {
+    pub const ALL: [BannerColour; 16] = {
+        use BannerColour::*;
+        [
+            White, Orange, Magenta, LightBlue, Yellow, Lime, Pink, Gray, LightGray, Cyan, Purple,
+            Blue, Brown, Green, Red, Black,
+        ]
+    };
+
+    /// The colour minecraft uses to draw banners, `None` for [`BannerColour::Unknown`].
+    pub const fn rgb(&self) -> Option<[u8; 3]> {
+        use BannerColour::*;
+        Some(match self {
+            White => [0xf9, 0xff, 0xfe],
+            Orange => [0xf9, 0x80, 0x1d],
+            Magenta => [0xc7, 0x4e, 0xbd],
+            LightBlue => [0x3a, 0xb3, 0xda],
+            Yellow => [0xfe, 0xd8, 0x3d],
+            Lime => [0x80, 0xc7, 0x1f],
+            Pink => [0xf3, 0x8b, 0xaa],
+            Gray => [0x47, 0x4f, 0x52],
+            LightGray => [0x9d, 0x9d, 0x97],
+            Cyan => [0x16, 0x9c, 0x9c],
+            Purple => [0x89, 0x32, 0xb8],
+            Blue => [0x3c, 0x44, 0xaa],
+            Brown => [0x83, 0x54, 0x32],
+            Green => [0x5e, 0x7c, 0x16],
+            Red => [0xb0, 0x2e, 0x26],
+            Black => [0x1d, 0x1d, 0x21],
+            Unknown(_) => return None,
+        })
+    }
+
+    /// The colour as `#rrggbb`.
+    pub fn hex(&self) -> Option<String> {
+        self.rgb()
+            .map(|[r, g, b]| format!("#{r:02x}{g:02x}{b:02x}"))
+    }
+}
+
+impl Display for BannerColour {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BannerColour::Unknown(s) => f.write_str(s),
+            colour => write!(f, "{colour:?}"),
+        }
+    }
+}
+
+/// The patterns of minecraft banners, named like the bukkit `PatternType`s the api uses.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum BannerPattern {
+    /// fills the whole banner
+    Base,
+    SquareBottomLeft,
+    SquareBottomRight,
+    SquareTopLeft,
+    SquareTopRight,
+    StripeBottom,
+    StripeTop,
+    StripeLeft,
+    StripeRight,
+    StripeCenter,
+    StripeMiddle,
+    StripeDownright,
+    StripeDownleft,
+    #[serde(alias = "STRIPE_SMALL")]
+    SmallStripes,
+    Cross,
+    StraightCross,
+    TriangleBottom,
+    TriangleTop,
+    TrianglesBottom,
+    TrianglesTop,
+    DiagonalLeft,
+    DiagonalRight,
+    #[serde(alias = "DIAGONAL_UP_LEFT")]
+    DiagonalLeftMirror,
+    #[serde(alias = "DIAGONAL_UP_RIGHT")]
+    DiagonalRightMirror,
+    #[serde(alias = "CIRCLE_MIDDLE")]
+    Circle,
+    #[serde(alias = "RHOMBUS_MIDDLE")]
+    Rhombus,
+    HalfVertical,
+    HalfHorizontal,
+    #[serde(alias = "HALF_VERTICAL_RIGHT")]
+    HalfVerticalMirror,
+    #[serde(alias = "HALF_HORIZONTAL_BOTTOM")]
+    HalfHorizontalMirror,
+    Border,
+    CurlyBorder,
+    Creeper,
+    Gradient,
+    GradientUp,
+    Bricks,
+    Skull,
+    Flower,
+    Mojang,
+    Globe,
+    Piglin,
+    Flow,
+    Guster,
+    /// a value this crate doesn't know about, only decoded in [lenient](crate::with_lenient) mode
+    #[serde(untagged, deserialize_with = "deserialize_unknown")]
+    Unknown(String),
+}
+
+impl BannerPattern {
+    pub const ALL: [BannerPattern; 43] = {
+        use BannerPattern::*;
+        [
+            Base,
+            SquareBottomLeft,
+            SquareBottomRight,
+            SquareTopLeft,
+            SquareTopRight,
+            StripeBottom,
+            StripeTop,
+            StripeLeft,
+            StripeRight,
+            StripeCenter,
+            StripeMiddle,
+            StripeDownright,
+            StripeDownleft,
+            SmallStripes,
+            Cross,
+            StraightCross,
+            TriangleBottom,
+            TriangleTop,
+            TrianglesBottom,
+            TrianglesTop,
+            DiagonalLeft,
+            DiagonalRight,
+            DiagonalLeftMirror,
+            DiagonalRightMirror,
+            Circle,
+            Rhombus,
+            HalfVertical,
+            HalfHorizontal,
+            HalfVerticalMirror,
+            HalfHorizontalMirror,
+            Border,
+            CurlyBorder,
+            Creeper,
+            Gradient,
+            GradientUp,
+            Bricks,
+            Skull,
+            Flower,
+            Mojang,
+            Globe,
+            Piglin,
+            Flow,
+            Guster,
+        ]
+    };
+}
+
+/// the size of a banner in pixels of the minecraft texture
+#[cfg(feature = "svg")]
+const WIDTH: f64 = 20.0;
+#[cfg(feature = "svg")]
+const HEIGHT: f64 = 40.0;
+
+#[cfg(feature = "svg")]
+#[derive(Debug, Clone)]
+enum Shape {
+    Rect { x: f64, y: f64, w: f64, h: f64 },
+    Polygon(Vec<(f64, f64)>),
+    Circle { x: f64, y: f64, r: f64 },
+}
+
+#[cfg(feature = "svg")]
+impl Shape {
+    #[cfg(feature = "png")]
+    fn contains(&self, px: f64, py: f64) -> bool {
+        match self {
+            Shape::Rect { x, y, w, h } => (*x..x + w).contains(&px) && (*y..y + h).contains(&py),
+            Shape::Circle { x, y, r } => (px - x).powi(2) + (py - y).powi(2) <= r * r,
+            // even-odd ray casting
+            Shape::Polygon(points) => {
+                let mut inside = false;
+                let mut j = points.len() - 1;
+                for i in 0..points.len() {
+                    let ((xi, yi), (xj, yj)) = (points[i], points[j]);
+                    if (yi > py) != (yj > py) && px < (xj - xi) * (py - yi) / (yj - yi) + xi {
+                        inside = !inside;
+                    }
+                    j = i;
+                }
+                inside
+            }
+        }
+    }
+
+    fn write_svg(&self, opacity: f64, svg: &mut String) {
+        use std::fmt::Write;
+
+        let opacity = if opacity < 1.0 {
+            format!(r#" fill-opacity="{}""#, Num(opacity))
+        } else {
+            String::new()
+        };
+        // writing to a string can't fail
+        let _ = match self {
+            Shape::Rect { x, y, w, h } => write!(
+                svg,
+                r#"<rect x="{}" y="{}" width="{}" height="{}"{opacity}/>"#,
+                Num(*x),
+                Num(*y),
+                Num(*w),
+                Num(*h)
+            ),
+            Shape::Circle { x, y, r } => write!(
+                svg,
+                r#"<circle cx="{}" cy="{}" r="{}"{opacity}/>"#,
+                Num(*x),
+                Num(*y),
+                Num(*r)
+            ),
+            Shape::Polygon(points) => {
+                let points: Vec<_> = points
+                    .iter()
+                    .map(|(x, y)| format!("{},{}", Num(*x), Num(*y)))
+                    .collect();
+                write!(svg, r#"<polygon points="{}"{opacity}/>"#, points.join(" "))
+            }
+        };
+    }
+}
+
+/// shapes with their opacity
+#[cfg(feature = "svg")]
+type Shapes = Vec<(Shape, f64)>;
+
+#[cfg(feature = "svg")]
+fn rect(x: f64, y: f64, w: f64, h: f64) -> (Shape, f64) {
+    (Shape::Rect { x, y, w, h }, 1.0)
+}
+
+#[cfg(feature = "svg")]
+fn polygon(points: &[(f64, f64)]) -> (Shape, f64) {
+    (Shape::Polygon(points.to_vec()), 1.0)
+}
+
+#[cfg(feature = "svg")]
+fn circle(x: f64, y: f64, r: f64) -> (Shape, f64) {
+    (Shape::Circle { x, y, r }, 1.0)
+}
+
+#[cfg(feature = "svg")]
+impl BannerPattern {
+    /// The shapes of the pattern on a 20 by 40 banner with their opacity.
+    ///
+    /// The geometric patterns match minecraft, the pictures (like [`BannerPattern::Creeper`]) are simplified.
+    fn shapes(&self) -> Shapes {
+        use BannerPattern::*;
+        let (w, h) = (WIDTH, HEIGHT);
+        let third = 13.0;
+        match self {
+            Base => vec![rect(0.0, 0.0, w, h)],
+            SquareBottomLeft => vec![rect(0.0, h - third, w / 2.0, third)],
+            SquareBottomRight => vec![rect(w / 2.0, h - third, w / 2.0, third)],
+            SquareTopLeft => vec![rect(0.0, 0.0, w / 2.0, third)],
+            SquareTopRight => vec![rect(w / 2.0, 0.0, w / 2.0, third)],
+            StripeBottom => vec![rect(0.0, h - third, w, third)],
+            StripeTop => vec![rect(0.0, 0.0, w, third)],
+            StripeLeft => vec![rect(0.0, 0.0, 7.0, h)],
+            StripeRight => vec![rect(w - 7.0, 0.0, 7.0, h)],
+            StripeCenter => vec![rect(7.0, 0.0, 6.0, h)],
+            StripeMiddle => vec![rect(0.0, 17.0, w, 6.0)],
+            StripeDownright => vec![polygon(&[
+                (0.0, 0.0),
+                (4.0, 0.0),
+                (w, h - 8.0),
+                (w, h),
+                (w - 4.0, h),
+                (0.0, 8.0),
+            ])],
+            StripeDownleft => vec![polygon(&[
+                (w, 0.0),
+                (w - 4.0, 0.0),
+                (0.0, h - 8.0),
+                (0.0, h),
+                (4.0, h),
+                (w, 8.0),
+            ])],
+            SmallStripes => [1.0, 6.0, 11.0, 16.0]
+                .map(|x| rect(x, 0.0, 3.0, h))
+                .to_vec(),
+            Cross => [StripeDownright, StripeDownleft]
+                .iter()
+                .flat_map(BannerPattern::shapes)
+                .collect(),
+            StraightCross => vec![rect(7.0, 0.0, 6.0, h), rect(0.0, 17.0, w, 6.0)],
+            TriangleBottom => vec![polygon(&[(0.0, h), (w / 2.0, h - 10.0), (w, h)])],
+            TriangleTop => vec![polygon(&[(0.0, 0.0), (w / 2.0, 10.0), (w, 0.0)])],
+            TrianglesBottom => {
+                let mut points = vec![(0.0, h)];
+                for i in 0..4 {
+                    let x = i as f64 * 5.0;
+                    points.extend([(x, h - 3.0), (x + 2.5, h - 6.0)]);
+                }
+                points.extend([(w, h - 3.0), (w, h)]);
+                vec![polygon(&points)]
+            }
+            TrianglesTop => {
+                let mut points = vec![(0.0, 0.0)];
+                for i in 0..4 {
+                    let x = i as f64 * 5.0;
+                    points.extend([(x, 3.0), (x + 2.5, 6.0)]);
+                }
+                points.extend([(w, 3.0), (w, 0.0)]);
+                vec![polygon(&points)]
+            }
+            DiagonalLeft => vec![polygon(&[(0.0, 0.0), (w, 0.0), (0.0, h)])],
+            DiagonalRight => vec![polygon(&[(0.0, 0.0), (w, h), (0.0, h)])],
+            DiagonalLeftMirror => vec![polygon(&[(w, 0.0), (w, h), (0.0, h)])],
+            DiagonalRightMirror => vec![polygon(&[(0.0, 0.0), (w, 0.0), (w, h)])],
+            Circle => vec![circle(w / 2.0, h / 2.0, 4.5)],
+            Rhombus => vec![polygon(&[
+                (w / 2.0, 11.0),
+                (17.0, h / 2.0),
+                (w / 2.0, 29.0),
+                (3.0, h / 2.0),
+            ])],
+            HalfVertical => vec![rect(0.0, 0.0, w / 2.0, h)],
+            HalfHorizontal => vec![rect(0.0, 0.0, w, h / 2.0)],
+            HalfVerticalMirror => vec![rect(w / 2.0, 0.0, w / 2.0, h)],
+            HalfHorizontalMirror => vec![rect(0.0, h / 2.0, w, h / 2.0)],
+            Border | CurlyBorder => {
+                let size = if *self == Border { 1.5 } else { 2.5 };
+                vec![
+                    rect(0.0, 0.0, w, size),
+                    rect(0.0, h - size, w, size),
+                    rect(0.0, size, size, h - 2.0 * size),
+                    rect(w - size, size, size, h - 2.0 * size),
+                ]
+            }
+            Gradient | GradientUp => (0..10)
+                .map(|i| {
+                    let band = if *self == Gradient { i } else { 9 - i };
+                    let (shape, _) = rect(0.0, i as f64 * 4.0, w, 4.0);
+                    (shape, 1.0 - band as f64 / 10.0)
+                })
+                .collect(),
+            Bricks => {
+                // the mortar between the bricks
+                let mut shapes: Vec<_> = (0..10)
+                    .map(|row| rect(0.0, row as f64 * 4.0 + 3.0, w, 1.0))
+                    .collect();
+                for row in 0..10 {
+                    let offset = if row % 2 == 0 { 0.0 } else { 4.0 };
+                    for x in [offset, offset + 8.0, offset + 16.0] {
+                        shapes.push(rect(x, row as f64 * 4.0, 1.0, 3.0));
+                    }
+                }
+                shapes
+            }
+            Creeper => vec![
+                rect(5.0, 13.0, 3.0, 3.0),
+                rect(12.0, 13.0, 3.0, 3.0),
+                rect(8.0, 16.0, 4.0, 5.0),
+                rect(6.0, 18.0, 2.0, 5.0),
+                rect(12.0, 18.0, 2.0, 5.0),
+            ],
+            Skull => vec![
+                rect(6.0, 11.0, 8.0, 7.0),
+                rect(8.0, 18.0, 4.0, 2.0),
+                polygon(&[(4.0, 21.0), (6.0, 21.0), (16.0, 28.0), (14.0, 28.0)]),
+                polygon(&[(16.0, 21.0), (14.0, 21.0), (4.0, 28.0), (6.0, 28.0)]),
+            ],
+            Flower => vec![
+                circle(w / 2.0, 15.0, 2.5),
+                circle(w / 2.0, 25.0, 2.5),
+                circle(5.0, h / 2.0, 2.5),
+                circle(15.0, h / 2.0, 2.5),
+                circle(w / 2.0, h / 2.0, 2.0),
+            ],
+            Mojang => vec![polygon(&[
+                (3.0, 26.0),
+                (3.0, 14.0),
+                (7.0, 14.0),
+                (10.0, 19.0),
+                (13.0, 14.0),
+                (17.0, 14.0),
+                (17.0, 26.0),
+                (14.0, 26.0),
+                (14.0, 20.0),
+                (10.0, 25.0),
+                (6.0, 20.0),
+                (6.0, 26.0),
+            ])],
+            Globe => vec![
+                circle(w / 2.0, h / 2.0, 6.0),
+                rect(3.0, 19.5, 14.0, 1.0),
+                rect(9.5, 13.0, 1.0, 14.0),
+            ],
+            Piglin => vec![
+                rect(6.0, 18.0, 8.0, 5.0),
+                rect(4.0, 13.0, 3.0, 2.0),
+                rect(13.0, 13.0, 3.0, 2.0),
+                rect(4.0, 24.0, 2.0, 3.0),
+                rect(14.0, 24.0, 2.0, 3.0),
+            ],
+            Flow => vec![circle(w / 2.0, h / 2.0, 7.0), rect(0.0, 19.0, w / 2.0, 2.0)],
+            Guster => vec![
+                rect(4.0, 14.0, 12.0, 3.0),
+                rect(6.0, 19.0, 8.0, 3.0),
+                rect(8.0, 24.0, 4.0, 3.0),
+            ],
+            Unknown(_) => Vec::new(),
+        }
+    }
+}
+
+#[cfg(feature = "svg")]
+impl Banner {
+    /// The layers to draw with their colour, the base is the first layer.
+    ///
+    /// Layers with an unknown colour are left out, an unknown base colour is drawn as white.
+    fn colour_layers(&self) -> Vec<([u8; 3], Shapes)> {
+        let base = self
+            .base
+            .rgb()
+            .unwrap_or(BannerColour::White.rgb().unwrap());
+        std::iter::once((base, BannerPattern::Base.shapes()))
+            .chain(
+                self.layers
+                    .iter()
+                    .filter_map(|layer| Some((layer.colour.rgb()?, layer.pattern.shapes()))),
+            )
+            .collect()
+    }
+
+    /// Renders the banner to an svg `scale` times the size of the minecraft texture (20 by 40).
+    ///
+    /// Only the cloth is drawn, not the pole or the [structure](Banner::structure) of the tier.
+    pub fn to_svg(&self, scale: f64) -> String {
+        let mut svg = format!(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{}" height="{}" viewBox="0 0 {} {}" shape-rendering="crispEdges">"#,
+            Num(WIDTH * scale),
+            Num(HEIGHT * scale),
+            Num(WIDTH),
+            Num(HEIGHT)
+        );
+        svg.push('\n');
+        for ([r, g, b], shapes) in self.colour_layers() {
+            svg.push_str(&format!(r##"<g fill="#{r:02x}{g:02x}{b:02x}">"##));
+            for (shape, opacity) in shapes {
+                shape.write_svg(opacity, &mut svg);
+            }
+            svg.push_str("</g>\n");
+        }
+        svg.push_str("</svg>\n");
+        svg
+    }
+
+    /// Renders the banner to a png `scale` times the size of the minecraft texture (20 by 40).
+    ///
+    /// Returns [`png::EncodingError::LimitsExceeded`] if the image would be too big to fit in memory.
+    #[cfg(feature = "png")]
+    pub fn to_png(&self, scale: u32) -> Result<Vec<u8>, png::EncodingError> {
+        let scale = scale.max(1);
+        let too_big = || png::EncodingError::LimitsExceeded;
+        let width = (WIDTH as u32).checked_mul(scale).ok_or_else(too_big)?;
+        let height = (HEIGHT as u32).checked_mul(scale).ok_or_else(too_big)?;
+        let size = (width as usize)
+            .checked_mul(height as usize)
+            .and_then(|pixels| pixels.checked_mul(3))
+            .ok_or_else(too_big)?;
+        let layers = self.colour_layers();
+        let mut pixels = Vec::new();
+        pixels.try_reserve_exact(size).map_err(|_| too_big())?;
+        for y in 0..height {
+            for x in 0..width {
+                // sample the centre of the pixel
+                let (px, py) = (
+                    (x as f64 + 0.5) / scale as f64,
+                    (y as f64 + 0.5) / scale as f64,
+                );
+                let mut colour = [0.0; 3];
+                for (rgb, shapes) in &layers {
+                    for (shape, opacity) in shapes {
+                        if shape.contains(px, py) {
+                            for (c, new) in colour.iter_mut().zip(rgb) {
+                                *c = *c * (1.0 - opacity) + *new as f64 * opacity;
+                            }
+                        }
+                    }
+                }
+                pixels.extend(colour.map(|c| c.round() as u8));
+            }
+        }
+
+        let mut bytes = Vec::new();
+        let mut encoder = png::Encoder::new(&mut bytes, width, height);
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder.write_header()?.write_image_data(&pixels)?;
+        Ok(bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{guild::BannerLayer, with_lenient};
+
+    #[test]
+    fn banner_types() {
+        let layers: Vec<BannerLayer> = serde_json::from_str(
+            r#"[{"colour": "LIGHT_BLUE", "pattern": "STRIPE_DOWNRIGHT"}, {"colour": "SILVER", "pattern": "CIRCLE_MIDDLE"}]"#,
+        )
+        .unwrap();
+        assert_eq!(layers[0].colour, BannerColour::LightBlue);
+        assert_eq!(layers[0].pattern, BannerPattern::StripeDownright);
+        assert_eq!(layers[1].colour, BannerColour::LightGray);
+        assert_eq!(layers[1].pattern, BannerPattern::Circle);
+        assert_eq!(BannerColour::Red.hex().unwrap(), "#b02e26");
+
+        let unknown = r#"{"colour": "TEAL", "pattern": "SNIFFER"}"#;
+        assert!(serde_json::from_str::<BannerLayer>(unknown).is_err());
+        let layer: BannerLayer = with_lenient(true, || serde_json::from_str(unknown)).unwrap();
+        assert_eq!(layer.colour, BannerColour::Unknown("TEAL".to_owned()));
+        assert_eq!(layer.colour.rgb(), None);
+        assert_eq!(
+            serde_json::to_string(&BannerPattern::SmallStripes).unwrap(),
+            r#""SMALL_STRIPES""#
+        );
+
+        // the names of newer bukkit versions
+        let pattern = |name| serde_json::from_str::<BannerPattern>(name).unwrap();
+        assert_eq!(
+            pattern(r#""DIAGONAL_UP_RIGHT""#),
+            BannerPattern::DiagonalRightMirror
+        );
+        assert_eq!(
+            pattern(r#""DIAGONAL_UP_LEFT""#),
+            BannerPattern::DiagonalLeftMirror
+        );
+    }
+
+    #[cfg(feature = "svg")]
+    fn banner() -> Banner {
+        serde_json::from_str(
+            r#"{"base": "BLACK", "tier": 2, "structure": "tier2", "layers": [
+                {"colour": "RED", "pattern": "HALF_HORIZONTAL"},
+                {"colour": "WHITE", "pattern": "CIRCLE"},
+                {"colour": "YELLOW", "pattern": "GRADIENT_UP"}
+            ]}"#,
+        )
+        .unwrap()
+    }
+
+    #[cfg(feature = "svg")]
+    #[test]
+    fn banner_svg() {
+        let svg = banner().to_svg(2.0);
+        let lines: Vec<_> = svg.lines().collect();
+        assert_eq!(
+            lines[0],
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="40" height="80" viewBox="0 0 20 40" shape-rendering="crispEdges">"#
+        );
+        assert_eq!(
+            lines[1],
+            r##"<g fill="#1d1d21"><rect x="0" y="0" width="20" height="40"/></g>"##
+        );
+        assert_eq!(
+            lines[2],
+            r##"<g fill="#b02e26"><rect x="0" y="0" width="20" height="20"/></g>"##
+        );
+        assert_eq!(
+            lines[3],
+            r##"<g fill="#f9fffe"><circle cx="10" cy="20" r="4.5"/></g>"##
+        );
+        assert!(lines[4].starts_with(
+            r##"<g fill="#fed83d"><rect x="0" y="0" width="20" height="4" fill-opacity="0.1"/>"##
+        ));
+        assert_eq!(lines.len(), 6);
+        // every pattern can be drawn
+        for pattern in BannerPattern::ALL {
+            assert!(!pattern.shapes().is_empty(), "{pattern:?}");
+        }
+    }
+
+    #[cfg(feature = "png")]
+    #[test]
+    fn banner_png() {
+        let png = banner().to_png(1).unwrap();
+        let decoder = png::Decoder::new(std::io::Cursor::new(png));
+        let mut reader = decoder.read_info().unwrap();
+        let mut pixels = vec![0; reader.output_buffer_size().unwrap()];
+        let info = reader.next_frame(&mut pixels).unwrap();
+        assert_eq!((info.width, info.height), (20, 40));
+        let pixel = |x: usize, y: usize| &pixels[(y * 20 + x) * 3..][..3];
+        // red top half, the faint yellow of the gradient is mixed in
+        assert_eq!(pixel(1, 1), [0xb8, 0x3f, 0x28]);
+        // the white circle, half covered by the gradient
+        assert_eq!(pixel(10, 20), [0xfc, 0xe8, 0x8a]);
+        // the black bottom is almost completely yellow
+        assert_eq!(pixel(1, 39), [0xfe, 0xd8, 0x3d]);
+    }
+
+    #[cfg(feature = "png")]
+    #[test]
+    fn png_too_big() {
+        assert!(matches!(
+            banner().to_png(u32::MAX),
+            Err(png::EncodingError::LimitsExceeded)
+        ));
+    }
+}
@@ -0,0 +1,291 @@
+use crate::Map;
+use reqwest::header::{HeaderMap, CACHE_CONTROL, EXPIRES};
+use std::{
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant, SystemTime},
+};
+
+/// Default bound of [`ResponseCache`], big enough to fit the full item database a few times.
+pub const DEFAULT_CACHE_SIZE: usize = 128 * 1024 * 1024;
+
+#[derive(Debug)]
+struct CacheEntry {
+    body: Arc<str>,
+    expires_at: Instant,
+    last_used: u64,
+}
+
+#[derive(Debug, Default)]
+struct CacheState {
+    entries: Map<String, CacheEntry>,
+    bytes: usize,
+    clock: u64,
+}
+
+impl CacheState {
+    fn remove(&mut self, url: &str) -> bool {
+        match self.entries.remove(url) {
+            Some(entry) => {
+                self.bytes -= entry.body.len();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// removes the least recently used entries until `extra` more bytes fit
+    fn make_room(&mut self, extra: usize, max_bytes: usize) {
+        while self.bytes + extra > max_bytes {
+            let Some(oldest) = self
+                .entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(url, _)| url.clone())
+            else {
+                return;
+            };
+            self.remove(&oldest);
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    pub entries: usize,
+    pub bytes: usize,
+}
+
+/// In memory cache of raw response bodies, keyed by url.
+///
+/// Entries are kept as long as the `Cache-Control` or `Expires` header of the response allows.
+#[derive(Debug)]
+pub struct ResponseCache {
+    state: Mutex<CacheState>,
+    max_bytes: usize,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl Default for ResponseCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_CACHE_SIZE)
+    }
+}
+
+impl ResponseCache {
+    /// `max_bytes` is the maximum total size of the cached bodies.
+    pub fn new(max_bytes: usize) -> Self {
+        Self {
+            state: Mutex::default(),
+            max_bytes,
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    /// Returns the cached body if it is still fresh, this counts as a hit or a miss.
+    pub fn get(&self, url: &str) -> Option<Arc<str>> {
+        let mut state = self.state.lock().expect("cache lock poisoned");
+        state.clock += 1;
+        let clock = state.clock;
+        let body = match state.entries.get_mut(url) {
+            Some(entry) if entry.expires_at > Instant::now() => {
+                entry.last_used = clock;
+                Some(entry.body.clone())
+            }
+            Some(_) => {
+                state.remove(url);
+                None
+            }
+            None => None,
+        };
+        match body {
+            Some(_) => self.hits.fetch_add(1, Ordering::Relaxed),
+            None => self.misses.fetch_add(1, Ordering::Relaxed),
+        };
+        body
+    }
+
+    /// Stores `body` for as long as `headers` allow, returns whether it was stored.
+    pub fn insert(&self, url: &str, headers: &HeaderMap, body: &str) -> bool {
+        match freshness(headers) {
+            Some(ttl) => self.insert_for(url, body, ttl),
+            None => false,
+        }
+    }
+
+    /// Stores `body` for `ttl`, returns false if it doesn't fit in the cache.
+    pub fn insert_for(&self, url: &str, body: &str, ttl: Duration) -> bool {
+        if body.len() > self.max_bytes || ttl.is_zero() {
+            return false;
+        }
+        let mut state = self.state.lock().expect("cache lock poisoned");
+        state.remove(url);
+        state.make_room(body.len(), self.max_bytes);
+        state.clock += 1;
+        let entry = CacheEntry {
+            body: body.into(),
+            expires_at: Instant::now() + ttl,
+            last_used: state.clock,
+        };
+        state.bytes += body.len();
+        state.entries.insert(url.to_owned(), entry);
+        true
+    }
+
+    /// Removes the entry of `url`, returns whether there was one.
+    pub fn invalidate(&self, url: &str) -> bool {
+        self.state.lock().expect("cache lock poisoned").remove(url)
+    }
+
+    /// Removes all entries of which the url starts with `prefix`.
+    pub fn invalidate_prefix(&self, prefix: &str) {
+        let mut state = self.state.lock().expect("cache lock poisoned");
+        let urls: Vec<String> = state
+            .entries
+            .keys()
+            .filter(|url| url.starts_with(prefix))
+            .cloned()
+            .collect();
+        for url in urls {
+            state.remove(&url);
+        }
+    }
+
+    pub fn clear(&self) {
+        let mut state = self.state.lock().expect("cache lock poisoned");
+        state.entries.clear();
+        state.bytes = 0;
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        let state = self.state.lock().expect("cache lock poisoned");
+        CacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            entries: state.entries.len(),
+            bytes: state.bytes,
+        }
+    }
+}
+
+/// Whether the headers forbid keeping the response at all, even as a stale fallback.
+pub fn no_store(headers: &HeaderMap) -> bool {
+    headers
+        .get_all(CACHE_CONTROL)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(','))
+        .any(|directive| directive.trim().eq_ignore_ascii_case("no-store"))
+}
+
+/// How long a response may be cached according to its headers.
+pub fn freshness(headers: &HeaderMap) -> Option<Duration> {
+    if let Some(cache_control) = headers.get(CACHE_CONTROL).and_then(|v| v.to_str().ok()) {
+        let mut max_age = None;
+        for directive in cache_control.split(',').map(str::trim) {
+            let directive = directive.to_ascii_lowercase();
+            if directive == "no-store" || directive == "no-cache" || directive == "private" {
+                return None;
+            }
+            if let Some(age) = directive.strip_prefix("max-age=") {
+                max_age = age.trim_matches('"').parse::<u64>().ok();
+            }
+        }
+        if let Some(age) = max_age {
+            return Some(Duration::from_secs(age));
+        }
+    }
+    let expires = headers.get(EXPIRES).and_then(|v| v.to_str().ok())?;
+    let expires = httpdate::parse_http_date(expires).ok()?;
+    expires.duration_since(SystemTime::now()).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        mock::{MockResponse, MockServer},
+        WynnClient,
+    };
+    use reqwest::header::HeaderValue;
+
+    fn headers(name: reqwest::header::HeaderName, value: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(name, HeaderValue::from_str(value).unwrap());
+        headers
+    }
+
+    #[test]
+    fn cache_headers() {
+        assert_eq!(
+            freshness(&headers(CACHE_CONTROL, "public, max-age=300")),
+            Some(Duration::from_secs(300))
+        );
+        assert_eq!(freshness(&headers(CACHE_CONTROL, "no-store")), None);
+        assert!(no_store(&headers(CACHE_CONTROL, "private, No-Store")));
+        assert!(!no_store(&headers(CACHE_CONTROL, "no-cache")));
+        assert_eq!(
+            freshness(&headers(EXPIRES, "Wed, 21 Oct 2015 07:28:00 GMT")),
+            None
+        );
+        let future = httpdate::fmt_http_date(SystemTime::now() + Duration::from_secs(120));
+        let ttl = freshness(&headers(EXPIRES, &future)).unwrap();
+        assert!(ttl > Duration::from_secs(100));
+        assert_eq!(freshness(&HeaderMap::new()), None);
+    }
+
+    #[test]
+    fn size_bound() {
+        let cache = ResponseCache::new(10);
+        let ttl = Duration::from_secs(60);
+        assert!(cache.insert_for("a", "12345", ttl));
+        assert!(cache.insert_for("b", "12345", ttl));
+        assert!(cache.get("a").is_some());
+        // `b` is the least recently used entry
+        assert!(cache.insert_for("c", "12345", ttl));
+        assert!(cache.get("b").is_none());
+        assert!(cache.get("a").is_some());
+        assert!(!cache.insert_for("d", "12345678901", ttl));
+        let stats = cache.stats();
+        assert_eq!(stats.entries, 2);
+        assert_eq!(stats.bytes, 10);
+        assert_eq!(stats.hits, 2);
+        assert_eq!(stats.misses, 1);
+    }
+
+    #[tokio::test]
+    async fn cached_requests() {
+        let server = MockServer::start(|request| {
+            let response = MockResponse::json("{}");
+            if request.path == "/guild/list/territory" {
+                response.with_header("Cache-Control", "max-age=60")
+            } else {
+                response.with_header("Cache-Control", "no-cache")
+            }
+        })
+        .await;
+        let client = WynnClient::builder()
+            .base_url(server.url())
+            .build()
+            .unwrap();
+        client.guild_teritories().await.unwrap();
+        client.guild_teritories().await.unwrap();
+        assert_eq!(server.request_count(), 1);
+        client.guild_list().await.unwrap();
+        client.guild_list().await.unwrap();
+        assert_eq!(server.request_count(), 3);
+
+        let cache = client.cache().unwrap();
+        let stats = cache.stats();
+        assert_eq!((stats.hits, stats.misses, stats.entries), (1, 3, 1));
+        assert!(cache.invalidate(&format!("{}/guild/list/territory", server.url())));
+        client.guild_teritories().await.unwrap();
+        assert_eq!(server.request_count(), 4);
+    }
+}
@@ -1,9 +1,9 @@
 use std::fmt::Display;
 
-use crate::{api_request, item::WeaponType, Map, WynnApiError, API_LOCATION};
+use crate::{deserialize_unknown, item::WeaponType, Map, WynnApiError, WynnClient};
 use serde::{Deserialize, Serialize};
 
-#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Copy)]
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
 #[serde(rename_all = "lowercase")]
 pub enum Class {
     #[serde(alias = "ARCHER")]
@@ -26,10 +26,13 @@ pub enum Class {
     Shaman,
     #[serde(alias = "SKYSEER")]
     Skyseer,
+    /// a value this crate doesn't know about, only decoded in [lenient](crate::with_lenient) mode
+    #[serde(untagged, deserialize_with = "deserialize_unknown")]
+    Unknown(String),
 }
 
 impl Class {
-    pub const fn main_class(self) -> Self {
+    pub fn main_class(&self) -> Self {
         use Class::*;
         match self {
             Hunter => Archer,
@@ -37,11 +40,11 @@ impl Class {
             DarkWizard => Mage,
             Ninja => Assassin,
             Skyseer => Shaman,
-            rest => rest,
+            rest => rest.clone(),
         }
     }
 
-    pub const fn donor_class(self) -> Self {
+    pub fn donor_class(&self) -> Self {
         use Class::*;
         match self {
             Archer => Hunter,
@@ -49,19 +52,21 @@ impl Class {
             Mage => DarkWizard,
             Assassin => Ninja,
             Shaman => Skyseer,
-            rest => rest,
+            rest => rest.clone(),
         }
     }
 
-    pub const fn weapon_type(self) -> WeaponType {
+    /// `None` for classes this crate doesn't know about
+    pub const fn weapon_type(&self) -> Option<WeaponType> {
         use Class::*;
-        match self {
+        Some(match self {
             Archer | Hunter => WeaponType::Bow,
             Warrior | Knight => WeaponType::Spear,
             Mage | DarkWizard => WeaponType::Wand,
             Assassin | Ninja => WeaponType::Dagger,
             Shaman | Skyseer => WeaponType::Wand,
-        }
+            Unknown(_) => return None,
+        })
     }
 }
 
@@ -112,12 +117,23 @@ pub struct Archetype {
     pub speed: u64,
 }
 
+impl WynnClient {
+    pub async fn class_list(&self) -> Result<ClassList, WynnApiError> {
+        self.api_request("/classes").await
+    }
+
+    pub async fn class_info(&self, class: Class) -> Result<ClassInfo, WynnApiError> {
+        self.api_request(&format!("/classes/{}", class.main_class()))
+            .await
+    }
+}
+
 pub async fn class_list() -> Result<ClassList, WynnApiError> {
-    api_request(&format!("{API_LOCATION}/classes")).await
+    WynnClient::global().class_list().await
 }
 
 pub async fn class_info(class: Class) -> Result<ClassInfo, WynnApiError> {
-    api_request(&format!("{API_LOCATION}/classes/{}", class.main_class())).await
+    WynnClient::global().class_info(class).await
 }
 
 #[cfg(test)]
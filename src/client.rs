@@ -0,0 +1,539 @@
+use crate::{
+    cache::{freshness, no_store, ResponseCache},
+    deserialize_data,
+    ratelimit::{retry_after, RateLimiter, RouteGroup},
+    retry::{ResponseMeta, RetryPolicy},
+    store::{CacheStore, StoredResponse},
+    with_lenient, WynnApiError, API_LOCATION, API_VERSION,
+};
+use reqwest::{
+    header::{HeaderMap, HeaderName, HeaderValue, USER_AGENT},
+    Client, RequestBuilder,
+};
+use serde::{de::DeserializeOwned, Serialize};
+use std::{
+    sync::{Arc, OnceLock},
+    time::{Duration, SystemTime},
+};
+
+static DEFAULT_CLIENT: OnceLock<WynnClient> = OnceLock::new();
+
+/// A client for the wynncraft api.
+///
+/// Every endpoint of the api is available as a method on this type,
+/// the free functions in the modules of this crate use [`WynnClient::global`].
+#[derive(Debug, Clone)]
+pub struct WynnClient {
+    client: Client,
+    base_url: String,
+    headers: HeaderMap,
+    timeout: Option<Duration>,
+    rate_limiter: Option<Arc<RateLimiter>>,
+    cache: Option<Arc<ResponseCache>>,
+    store: Option<Arc<dyn CacheStore>>,
+    offline: bool,
+    lenient: bool,
+    retry: RetryPolicy,
+}
+
+/// A response of the api before it is deserialized.
+struct RawResponse {
+    text: String,
+    headers: HeaderMap,
+    multi_selector: bool,
+    /// how many times the request was sent
+    attempts: u32,
+}
+
+impl Default for WynnClient {
+    fn default() -> Self {
+        Self {
+            client: Client::new(),
+            base_url: API_LOCATION.to_owned(),
+            headers: HeaderMap::new(),
+            timeout: None,
+            rate_limiter: Some(Arc::new(RateLimiter::new())),
+            cache: Some(Arc::new(ResponseCache::default())),
+            store: None,
+            offline: false,
+            lenient: false,
+            retry: RetryPolicy::never(),
+        }
+    }
+}
+
+impl WynnClient {
+    /// Creates a client that talks to [`API_LOCATION`] with default settings.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[inline]
+    pub fn builder() -> WynnClientBuilder {
+        WynnClientBuilder::new()
+    }
+
+    /// The client used by the free functions of this crate.
+    pub fn global() -> &'static WynnClient {
+        DEFAULT_CLIENT.get_or_init(WynnClient::new)
+    }
+
+    #[inline]
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    #[inline]
+    pub fn rate_limiter(&self) -> Option<&RateLimiter> {
+        self.rate_limiter.as_deref()
+    }
+
+    #[inline]
+    pub fn cache(&self) -> Option<&ResponseCache> {
+        self.cache.as_deref()
+    }
+
+    #[inline]
+    pub fn store(&self) -> Option<&dyn CacheStore> {
+        self.store.as_deref()
+    }
+
+    /// Whether this client only answers requests from its cache and store.
+    #[inline]
+    pub fn is_offline(&self) -> bool {
+        self.offline
+    }
+
+    fn decode<T>(&self, link: &str, text: &str, multi_selector: bool) -> Result<T, WynnApiError>
+    where
+        T: DeserializeOwned,
+    {
+        with_lenient(self.lenient, || {
+            deserialize_data(link, text, multi_selector)
+        })
+    }
+
+    /// Sends a request to any endpoint of the api, `path` is relative to the base url.
+    ///
+    /// Combined with [`Lossless`](crate::Lossless) this also returns the raw json of the response.
+    pub async fn request<T>(&self, path: &str) -> Result<T, WynnApiError>
+    where
+        T: DeserializeOwned,
+    {
+        self.api_request(path).await
+    }
+
+    /// Like [`request`](Self::request), but also returns how the request was answered.
+    pub async fn request_with_meta<T>(&self, path: &str) -> Result<(T, ResponseMeta), WynnApiError>
+    where
+        T: DeserializeOwned,
+    {
+        self.api_request_with_meta(path).await
+    }
+
+    /// A copy of this client that uses `policy` to retry requests, for when a single call needs a different policy.
+    pub fn with_retry(&self, policy: RetryPolicy) -> WynnClient {
+        WynnClient {
+            retry: policy,
+            ..self.clone()
+        }
+    }
+
+    #[inline]
+    pub fn retry_policy(&self) -> &RetryPolicy {
+        &self.retry
+    }
+
+    /// Sends the request until it succeeds or the retry policy gives up.
+    async fn execute(
+        &self,
+        path: &str,
+        request: RequestBuilder,
+    ) -> Result<RawResponse, WynnApiError> {
+        let mut attempt = 0;
+        loop {
+            attempt += 1;
+            let this_request = request
+                .try_clone()
+                .expect("requests of this crate can be cloned");
+            match self.send_once(path, this_request).await {
+                Err(e) if attempt < self.retry.max_attempts && self.retry.should_retry(&e) => {
+                    tokio::time::sleep(self.retry.delay(attempt, &e)).await;
+                }
+                result => {
+                    return result.map(|response| RawResponse {
+                        attempts: attempt,
+                        ..response
+                    })
+                }
+            }
+        }
+    }
+
+    async fn send_once(
+        &self,
+        path: &str,
+        request: RequestBuilder,
+    ) -> Result<RawResponse, WynnApiError> {
+        let group = RouteGroup::from_path(path);
+        if let Some(limiter) = &self.rate_limiter {
+            limiter.acquire(group).await;
+        }
+        let mut request = request.headers(self.headers.clone());
+        if let Some(timeout) = self.timeout {
+            request = request.timeout(timeout);
+        }
+        let response = match request.send().await {
+            Ok(response) => response,
+            Err(e) => {
+                if let Some(limiter) = &self.rate_limiter {
+                    limiter.cancel(group);
+                }
+                return Err(e.into());
+            }
+        };
+        if let Some(limiter) = &self.rate_limiter {
+            limiter.update(group, response.status().as_u16(), response.headers());
+        }
+        let status = response.status();
+        if status.is_client_error() || status.is_server_error() {
+            let retry_after = retry_after(response.headers());
+            let url = response.url().to_string();
+            let text = response.text().await.unwrap_or_default();
+            let body = serde_json::from_str(&text).ok();
+            return Err(WynnApiError::from_status(
+                status.as_u16(),
+                &url,
+                retry_after,
+                body,
+            ));
+        }
+        let multi_selector = response.status().is_redirection();
+        let headers = response.headers().clone();
+        let text = response.text().await?;
+        Ok(RawResponse {
+            text,
+            headers,
+            multi_selector,
+            attempts: 1,
+        })
+    }
+
+    /// `path` is relative to the base url and has to start with a `/`
+    pub(crate) async fn api_request<T>(&self, path: &str) -> Result<T, WynnApiError>
+    where
+        T: DeserializeOwned,
+    {
+        Ok(self.api_request_with_meta(path).await?.0)
+    }
+
+    /// `path` is relative to the base url and has to start with a `/`
+    pub(crate) async fn api_request_with_meta<T>(
+        &self,
+        path: &str,
+    ) -> Result<(T, ResponseMeta), WynnApiError>
+    where
+        T: DeserializeOwned,
+    {
+        let cached = |link: &str| ResponseMeta {
+            url: link.to_owned(),
+            attempts: 0,
+            from_cache: true,
+        };
+        let link = format!("{}{path}", self.base_url);
+        if let Some(text) = self.cache.as_ref().and_then(|cache| cache.get(&link)) {
+            return Ok((self.decode(&link, &text, false)?, cached(&link)));
+        }
+        if let Some(store) = &self.store {
+            let stored = match store.load(&link) {
+                Ok(stored) => stored,
+                Err(err) if self.offline => return Err(err.into()),
+                // online the request can still go to the api, like when saving fails
+                Err(_) => None,
+            };
+            let stored = stored.filter(|stored| stored.api_version == API_VERSION);
+            if let Some(stored) = stored {
+                if self.offline || stored.is_fresh() {
+                    return Ok((self.decode(&link, &stored.body, false)?, cached(&link)));
+                }
+            }
+        }
+        if self.offline {
+            return Err(WynnApiError::OfflineMiss(link));
+        }
+        let response = self.execute(path, self.client.get(&link)).await?;
+        if !response.multi_selector {
+            if let Some(cache) = &self.cache {
+                cache.insert(&link, &response.headers, &response.text);
+            }
+            if let Some(store) = self.store.as_ref().filter(|_| !no_store(&response.headers)) {
+                let fetched_at = SystemTime::now();
+                // the response is already here, failing to keep it for later shouldn't fail the request
+                let _ = store.save(&StoredResponse {
+                    url: link.clone(),
+                    body: response.text.clone(),
+                    fetched_at,
+                    expires_at: freshness(&response.headers).map(|ttl| fetched_at + ttl),
+                    api_version: API_VERSION.to_owned(),
+                });
+            }
+        }
+        let data = self.decode(&link, &response.text, response.multi_selector)?;
+        let meta = ResponseMeta {
+            url: link,
+            attempts: response.attempts,
+            from_cache: false,
+        };
+        Ok((data, meta))
+    }
+
+    /// `path` is relative to the base url and has to start with a `/`
+    pub(crate) async fn post_api_request<T, Q>(
+        &self,
+        path: &str,
+        content: Q,
+    ) -> Result<T, WynnApiError>
+    where
+        T: DeserializeOwned,
+        Q: Serialize,
+    {
+        let link = format!("{}{path}", self.base_url);
+        if self.offline {
+            return Err(WynnApiError::OfflineMiss(link));
+        }
+        let body = serde_json::to_string(&content).expect("valid serializable data");
+        let request = self
+            .client
+            .post(&link)
+            .body(body)
+            .header("Content-Type", "application/json");
+        let response = self.execute(path, request).await?;
+        self.decode(&link, &response.text, response.multi_selector)
+    }
+}
+
+/// Builder for a [`WynnClient`].
+#[derive(Debug, Default)]
+pub struct WynnClientBuilder {
+    client: Option<Client>,
+    base_url: Option<String>,
+    headers: HeaderMap,
+    timeout: Option<Duration>,
+    connect_timeout: Option<Duration>,
+    rate_limiter: Option<RateLimiter>,
+    no_rate_limiter: bool,
+    cache: Option<ResponseCache>,
+    no_cache: bool,
+    store: Option<Arc<dyn CacheStore>>,
+    offline: bool,
+    lenient: bool,
+    retry: Option<RetryPolicy>,
+}
+
+impl WynnClientBuilder {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Use a different location for the api, for example a proxy or a mock server.
+    /// Defaults to [`API_LOCATION`].
+    pub fn base_url(mut self, base_url: impl Into<String>) -> Self {
+        let mut base_url = base_url.into();
+        while base_url.ends_with('/') {
+            base_url.pop();
+        }
+        self.base_url = Some(base_url);
+        self
+    }
+
+    pub fn user_agent(self, user_agent: HeaderValue) -> Self {
+        self.default_header(USER_AGENT, user_agent)
+    }
+
+    /// Timeout for an entire request, from connecting until the body is read.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Only used when no custom client is set with [`WynnClientBuilder::client`].
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = Some(timeout);
+        self
+    }
+
+    /// Header that is sent with every request.
+    pub fn default_header(mut self, name: HeaderName, value: HeaderValue) -> Self {
+        self.headers.insert(name, value);
+        self
+    }
+
+    pub fn default_headers(mut self, headers: HeaderMap) -> Self {
+        self.headers.extend(headers);
+        self
+    }
+
+    /// Use an already configured [`reqwest::Client`] to send the requests.
+    pub fn client(mut self, client: Client) -> Self {
+        self.client = Some(client);
+        self
+    }
+
+    /// Use a rate limiter with custom budgets, by default a [`RateLimiter`] that only follows the headers of the api is used.
+    pub fn rate_limiter(mut self, rate_limiter: RateLimiter) -> Self {
+        self.rate_limiter = Some(rate_limiter);
+        self.no_rate_limiter = false;
+        self
+    }
+
+    /// Send requests without waiting for the rate limit of the api.
+    pub fn without_rate_limiter(mut self) -> Self {
+        self.rate_limiter = None;
+        self.no_rate_limiter = true;
+        self
+    }
+
+    /// Use a cache with a custom size, by default a [`ResponseCache`] with the [default size](crate::cache::DEFAULT_CACHE_SIZE) is used.
+    pub fn cache(mut self, cache: ResponseCache) -> Self {
+        self.cache = Some(cache);
+        self.no_cache = false;
+        self
+    }
+
+    /// Always send requests to the api, even when the last response is still fresh.
+    pub fn without_cache(mut self) -> Self {
+        self.cache = None;
+        self.no_cache = true;
+        self
+    }
+
+    /// Save every response in a persistent store, fresh responses in the store are used instead of calling the api.
+    pub fn store(mut self, store: impl CacheStore + 'static) -> Self {
+        self.store = Some(Arc::new(store));
+        self
+    }
+
+    /// Only answer requests from the cache and the store, a request that isn't in there
+    /// returns [`WynnApiError::OfflineMiss`], even when the stored response is outdated.
+    pub fn offline(mut self, offline: bool) -> Self {
+        self.offline = offline;
+        self
+    }
+
+    /// Decode values of enums that this crate doesn't know yet into their `Unknown` variant,
+    /// instead of failing the whole request.
+    pub fn lenient(mut self, lenient: bool) -> Self {
+        self.lenient = lenient;
+        self
+    }
+
+    /// Retry failed requests, by default every request is only sent once.
+    pub fn retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.retry = Some(policy);
+        self
+    }
+
+    pub fn build(self) -> Result<WynnClient, WynnApiError> {
+        let client = match self.client {
+            Some(client) => client,
+            None => {
+                let mut builder = Client::builder();
+                if let Some(timeout) = self.connect_timeout {
+                    builder = builder.connect_timeout(timeout);
+                }
+                builder.build()?
+            }
+        };
+        Ok(WynnClient {
+            client,
+            base_url: self.base_url.unwrap_or_else(|| API_LOCATION.to_owned()),
+            headers: self.headers,
+            timeout: self.timeout,
+            rate_limiter: (!self.no_rate_limiter)
+                .then(|| Arc::new(self.rate_limiter.unwrap_or_default())),
+            cache: (!self.no_cache).then(|| Arc::new(self.cache.unwrap_or_default())),
+            store: self.store,
+            offline: self.offline,
+            lenient: self.lenient,
+            retry: self.retry.unwrap_or_else(RetryPolicy::never),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::{MockResponse, MockServer};
+
+    #[tokio::test]
+    async fn custom_base_url() {
+        let server = MockServer::start(|request| {
+            assert_eq!(request.path, "/news/latest-news");
+            assert_eq!(request.header("user-agent"), Some("wynn-test"));
+            assert_eq!(request.header("x-custom"), Some("yes"));
+            MockResponse::json("[]")
+        })
+        .await;
+        let client = WynnClient::builder()
+            .base_url(format!("{}/news/", server.url()))
+            .user_agent(HeaderValue::from_static("wynn-test"))
+            .default_header(
+                HeaderName::from_static("x-custom"),
+                HeaderValue::from_static("yes"),
+            )
+            .timeout(Duration::from_secs(5))
+            .build()
+            .unwrap();
+        let news = client.latest_news().await.unwrap();
+        assert!(news.is_empty());
+        assert_eq!(server.request_count(), 1);
+    }
+
+    #[tokio::test]
+    async fn error_statuses() {
+        let server = MockServer::start(|request| match request.path.as_str() {
+            "/player/nobody" => MockResponse::status(404, r#"{"error": "Player not found"}"#),
+            "/guild/list/guild" => MockResponse::status(429, "").with_header("Retry-After", 3),
+            _ => MockResponse::status(500, "oops"),
+        })
+        .await;
+        let client = WynnClient::builder()
+            .base_url(server.url())
+            .without_rate_limiter()
+            .build()
+            .unwrap();
+
+        let not_found = client.player_main_stats("nobody").await.unwrap_err();
+        assert!(matches!(not_found, WynnApiError::NotFound { .. }));
+        assert_eq!(not_found.body().unwrap()["error"], "Player not found");
+        assert!(!not_found.is_retryable());
+
+        let limited = client.guild_list().await.unwrap_err();
+        assert_eq!(limited.retry_after(), Some(Duration::from_secs(3)));
+        assert!(limited.is_retryable());
+
+        let server_error = client.latest_news().await.unwrap_err();
+        assert!(matches!(
+            server_error,
+            WynnApiError::ServerError {
+                status: 500,
+                body: None,
+                ..
+            }
+        ));
+        assert!(server_error.is_retryable());
+    }
+
+    #[tokio::test]
+    async fn transport_error() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        drop(listener);
+        let client = WynnClient::builder().base_url(url).build().unwrap();
+        let error = client.latest_news().await.unwrap_err();
+        assert!(matches!(error, WynnApiError::Transport(_)));
+        assert!(error.is_retryable());
+    }
+}
@@ -0,0 +1,329 @@
+//! Territory geometry: rectangles, a lookup of the territory at a coordinate and the graph of neighbouring territories.
+//!
+//! Coordinates are minecraft block coordinates, the map is the `x`/`z` plane.
+
+use crate::{guild::Territory, Map};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+
+/// A rectangle on the map with `min <= max` on both axes.
+///
+/// The edges are half-open like a range: the `min` edges are part of the rectangle and the `max` edges aren't,
+/// so `width`, `depth` and `area` are `max - min` and rectangles that share an edge don't overlap.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone, Copy)]
+#[serde(rename_all = "camelCase")]
+pub struct Rect {
+    pub min_x: i64,
+    pub min_z: i64,
+    pub max_x: i64,
+    pub max_z: i64,
+}
+
+impl Rect {
+    /// The rectangle between two opposite corners, in any order.
+    pub fn new(a: (i64, i64), b: (i64, i64)) -> Self {
+        Self {
+            min_x: a.0.min(b.0),
+            min_z: a.1.min(b.1),
+            max_x: a.0.max(b.0),
+            max_z: a.1.max(b.1),
+        }
+    }
+
+    pub fn width(&self) -> i64 {
+        self.max_x - self.min_x
+    }
+
+    pub fn depth(&self) -> i64 {
+        self.max_z - self.min_z
+    }
+
+    pub fn area(&self) -> i64 {
+        self.width() * self.depth()
+    }
+
+    pub fn centre(&self) -> (f64, f64) {
+        (
+            (self.min_x + self.max_x) as f64 / 2.0,
+            (self.min_z + self.max_z) as f64 / 2.0,
+        )
+    }
+
+    pub fn contains(&self, x: i64, z: i64) -> bool {
+        (self.min_x..self.max_x).contains(&x) && (self.min_z..self.max_z).contains(&z)
+    }
+
+    /// Whether the rectangles overlap, sharing an edge isn't enough.
+    pub fn intersects(&self, other: &Rect) -> bool {
+        self.min_x < other.max_x
+            && other.min_x < self.max_x
+            && self.min_z < other.max_z
+            && other.min_z < self.max_z
+    }
+
+    /// Whether the rectangles share a border, or would if they were at most `gap` blocks closer.
+    ///
+    /// Rectangles that only meet at a corner are not adjacent.
+    pub fn is_adjacent(&self, other: &Rect, gap: i64) -> bool {
+        let dist_x = (other.min_x - self.max_x).max(self.min_x - other.max_x);
+        let dist_z = (other.min_z - self.max_z).max(self.min_z - other.max_z);
+        // a negative distance is the length of the overlap on that axis
+        (dist_x <= gap && dist_z < 0) || (dist_z <= gap && dist_x < 0)
+    }
+}
+
+/// the size of the cells of a [`TerritoryIndex`], territories are a few hundred blocks wide
+const CELL_SIZE: i64 = 256;
+
+fn cell(x: i64, z: i64) -> (i64, i64) {
+    (x.div_euclid(CELL_SIZE), z.div_euclid(CELL_SIZE))
+}
+
+/// Something with a position on the map.
+pub trait MapPosition {
+    /// The `(x, z)` block coordinates.
+    fn position(&self) -> (i64, i64);
+
+    /// The territory this is in.
+    fn territory<'a>(&self, index: &'a TerritoryIndex) -> Option<&'a str> {
+        let (x, z) = self.position();
+        index.territory_at(x, z)
+    }
+}
+
+/// Finds the territory at a coordinate without checking every territory.
+///
+/// Territories are put in a grid of cells, a lookup only checks the territories that overlap the cell of the coordinate.
+#[derive(Debug, Clone, Default)]
+pub struct TerritoryIndex {
+    /// sorted by name
+    territories: Vec<(String, Rect)>,
+    cells: Map<(i64, i64), Vec<usize>>,
+}
+
+impl TerritoryIndex {
+    pub fn new(territories: impl IntoIterator<Item = (String, Rect)>) -> Self {
+        let mut territories: Vec<_> = territories.into_iter().collect();
+        territories.sort_by(|a, b| a.0.cmp(&b.0));
+        let mut cells: Map<_, Vec<_>> = Map::new();
+        for (i, (_, rect)) in territories.iter().enumerate() {
+            let (min, max) = (cell(rect.min_x, rect.min_z), cell(rect.max_x, rect.max_z));
+            for x in min.0..=max.0 {
+                for z in min.1..=max.1 {
+                    cells.entry((x, z)).or_default().push(i);
+                }
+            }
+        }
+        Self { territories, cells }
+    }
+
+    /// Builds the index from the result of [`guild_teritories`](crate::WynnClient::guild_teritories).
+    pub fn from_territories(territories: &Map<String, Territory>) -> Self {
+        Self::new(
+            territories
+                .iter()
+                .map(|(name, territory)| (name.clone(), territory.location.rect())),
+        )
+    }
+
+    /// The name of the territory at the coordinate.
+    ///
+    /// Where territories overlap the smallest one is picked, so a territory inside another one can still be found.
+    pub fn territory_at(&self, x: i64, z: i64) -> Option<&str> {
+        self.cells
+            .get(&cell(x, z))?
+            .iter()
+            .map(|&i| &self.territories[i])
+            .filter(|(_, rect)| rect.contains(x, z))
+            .min_by_key(|(_, rect)| rect.area())
+            .map(|(name, _)| name.as_str())
+    }
+
+    pub fn rect(&self, territory: &str) -> Option<Rect> {
+        self.territories
+            .binary_search_by(|(name, _)| name.as_str().cmp(territory))
+            .ok()
+            .map(|i| self.territories[i].1)
+    }
+
+    /// Every territory with its rectangle, sorted by name.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, Rect)> {
+        self.territories
+            .iter()
+            .map(|(name, rect)| (name.as_str(), *rect))
+    }
+
+    pub fn len(&self) -> usize {
+        self.territories.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.territories.is_empty()
+    }
+
+    /// Connects every pair of territories that are adjacent with at most `gap` blocks between them,
+    /// see [`Rect::is_adjacent`].
+    pub fn adjacency(&self, gap: i64) -> TerritoryGraph {
+        let mut neighbours: BTreeMap<String, BTreeSet<String>> = self
+            .territories
+            .iter()
+            .map(|(name, _)| (name.clone(), BTreeSet::new()))
+            .collect();
+        for (i, (a, rect_a)) in self.territories.iter().enumerate() {
+            for (b, rect_b) in &self.territories[i + 1..] {
+                if rect_a.is_adjacent(rect_b, gap) {
+                    neighbours.get_mut(a).unwrap().insert(b.clone());
+                    neighbours.get_mut(b).unwrap().insert(a.clone());
+                }
+            }
+        }
+        TerritoryGraph { neighbours }
+    }
+}
+
+/// Which territories border which, derived from their rectangles by [`TerritoryIndex::adjacency`].
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Default)]
+pub struct TerritoryGraph {
+    neighbours: BTreeMap<String, BTreeSet<String>>,
+}
+
+impl TerritoryGraph {
+    /// The neighbours of `territory`, empty if it isn't in the graph.
+    pub fn neighbours(&self, territory: &str) -> impl Iterator<Item = &str> {
+        self.neighbours
+            .get(territory)
+            .into_iter()
+            .flatten()
+            .map(String::as_str)
+    }
+
+    pub fn are_adjacent(&self, a: &str, b: &str) -> bool {
+        self.neighbours.get(a).is_some_and(|n| n.contains(b))
+    }
+
+    /// Every pair of neighbours once, sorted.
+    pub fn edges(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.neighbours.iter().flat_map(|(a, neighbours)| {
+            neighbours
+                .iter()
+                .filter(move |b| a < *b)
+                .map(move |b| (a.as_str(), b.as_str()))
+        })
+    }
+
+    /// One of the shortest paths from `from` to `to` including both ends, `None` if they aren't connected.
+    pub fn path(&self, from: &str, to: &str) -> Option<Vec<&str>> {
+        let (from, _) = self.neighbours.get_key_value(from)?;
+        let mut previous: BTreeMap<&str, &str> = BTreeMap::new();
+        let mut queue = VecDeque::from([from.as_str()]);
+        previous.insert(from, from);
+        while let Some(current) = queue.pop_front() {
+            if current == to {
+                let mut path = vec![current];
+                let mut current = current;
+                while current != from {
+                    current = previous[current];
+                    path.push(current);
+                }
+                path.reverse();
+                return Some(path);
+            }
+            for next in self.neighbours(current) {
+                if !previous.contains_key(next) {
+                    previous.insert(next, current);
+                    queue.push_back(next);
+                }
+            }
+        }
+        None
+    }
+
+    /// The amount of territories between `from` and `to`, `None` if they aren't connected.
+    pub fn distance(&self, from: &str, to: &str) -> Option<usize> {
+        self.path(from, to).map(|path| path.len() - 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn index() -> TerritoryIndex {
+        // a row of three territories with Ragni in the middle, and a camp inside Detlas
+        TerritoryIndex::new([
+            ("Maltic".to_owned(), Rect::new((0, 0), (100, 100))),
+            ("Ragni".to_owned(), Rect::new((200, 100), (101, 0))),
+            ("Detlas".to_owned(), Rect::new((201, 0), (400, 100))),
+            ("Detlas Camp".to_owned(), Rect::new((300, 40), (320, 60))),
+            (
+                "Nemract".to_owned(),
+                Rect::new((-1000, -1000), (-900, -900)),
+            ),
+            ("Corner".to_owned(), Rect::new((401, 101), (500, 200))),
+        ])
+    }
+
+    #[test]
+    fn rects() {
+        let rect = Rect::new((200, 100), (101, 0));
+        assert_eq!(
+            rect,
+            Rect {
+                min_x: 101,
+                min_z: 0,
+                max_x: 200,
+                max_z: 100
+            }
+        );
+        assert_eq!(rect.area(), 9900);
+        assert_eq!(rect.centre(), (150.5, 50.0));
+        assert!(rect.contains(101, 0));
+        assert!(rect.contains(199, 99));
+        assert!(!rect.contains(200, 50));
+        assert!(!rect.contains(100, 50));
+        assert!(rect.intersects(&Rect::new((150, 50), (160, 60))));
+        // a shared edge isn't an overlap
+        assert!(!rect.intersects(&Rect::new((200, 0), (300, 100))));
+        let block = Rect::new((5, 5), (6, 6));
+        assert_eq!(block.area(), 1);
+        assert!(block.contains(5, 5) && !block.contains(6, 6));
+    }
+
+    #[test]
+    fn lookup() {
+        let index = index();
+        assert_eq!(index.territory_at(50, 50), Some("Maltic"));
+        assert_eq!(index.territory_at(101, 0), Some("Ragni"));
+        assert_eq!(index.territory_at(250, 20), Some("Detlas"));
+        assert_eq!(index.territory_at(310, 50), Some("Detlas Camp"));
+        assert_eq!(index.territory_at(-950, -950), Some("Nemract"));
+        assert_eq!(index.territory_at(-500, 0), None);
+        assert_eq!(index.rect("Ragni").unwrap().min_x, 101);
+
+        let marker: crate::map::MarkerLocation = serde_json::from_str(
+            r#"{"name": "Ragni Bank", "icon": "Content_Bank.png", "x": "150", "y": "64", "z": "25"}"#,
+        )
+        .unwrap();
+        assert_eq!(marker.territory(&index), Some("Ragni"));
+    }
+
+    #[test]
+    fn graph() {
+        let graph = index().adjacency(1);
+        assert!(graph.are_adjacent("Maltic", "Ragni"));
+        assert!(graph.are_adjacent("Detlas", "Detlas Camp"));
+        // only touches Detlas at a corner
+        assert_eq!(graph.neighbours("Corner").count(), 0);
+        assert_eq!(
+            graph.neighbours("Detlas").collect::<Vec<_>>(),
+            ["Detlas Camp", "Ragni"]
+        );
+        assert_eq!(graph.edges().count(), 3);
+        assert_eq!(
+            graph.path("Maltic", "Detlas Camp"),
+            Some(vec!["Maltic", "Ragni", "Detlas", "Detlas Camp"])
+        );
+        assert_eq!(graph.distance("Maltic", "Maltic"), Some(0));
+        assert_eq!(graph.path("Maltic", "Nemract"), None);
+    }
+}
@@ -1,12 +1,19 @@
+#[cfg(feature = "chrono")]
+use crate::timestamp::{self, InvalidTimestamp};
 use crate::{
-    api_request, deserialize_with_default, Identifier, Map, World, WynnApiError, API_LOCATION,
+    banner::{BannerColour, BannerPattern},
+    deserialize_with_default,
+    geometry::Rect,
+    uuid::{GuildUuid, PlayerUuid},
+    Identifier, Map, World, WynnApiError, WynnClient,
 };
 use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, fmt::Display};
 
 #[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct Guild {
-    pub uuid: String,
+    pub uuid: GuildUuid,
     pub name: String,
     #[serde(default, deserialize_with = "deserialize_with_default")]
     pub prefix: String,
@@ -22,6 +29,63 @@ pub struct Guild {
     pub season_ranks: Map<String, SeasonRank>,
 }
 
+/// The rank of a member in a guild, ordered from [`GuildRank::Recruit`] to [`GuildRank::Owner`].
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Clone, Copy)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum GuildRank {
+    #[serde(alias = "recruit")]
+    Recruit,
+    #[serde(alias = "recruiter")]
+    Recruiter,
+    #[serde(alias = "captain")]
+    Captain,
+    #[serde(alias = "strategist")]
+    Strategist,
+    #[serde(alias = "chief")]
+    Chief,
+    #[serde(alias = "owner")]
+    Owner,
+}
+
+impl GuildRank {
+    /// From the lowest to the highest rank.
+    pub const ALL: [GuildRank; 6] = [
+        GuildRank::Recruit,
+        GuildRank::Recruiter,
+        GuildRank::Captain,
+        GuildRank::Strategist,
+        GuildRank::Chief,
+        GuildRank::Owner,
+    ];
+
+    /// The amount of stars shown in front of the name of a member with this rank.
+    pub const fn stars(&self) -> usize {
+        *self as usize
+    }
+
+    /// Parses a rank from its stars, like `**` for a captain.
+    pub fn from_stars(stars: &str) -> Option<Self> {
+        let stars = stars.trim();
+        if !stars.chars().all(|c| c == '*' || c == '★') {
+            return None;
+        }
+        GuildRank::ALL.get(stars.chars().count()).copied()
+    }
+}
+
+impl Display for GuildRank {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            GuildRank::Recruit => "Recruit",
+            GuildRank::Recruiter => "Recruiter",
+            GuildRank::Captain => "Captain",
+            GuildRank::Strategist => "Strategist",
+            GuildRank::Chief => "Chief",
+            GuildRank::Owner => "Owner",
+        })
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct GuildMembers {
@@ -34,13 +98,70 @@ pub struct GuildMembers {
     pub recruit: Map<String, GuildPlayerInfo>,
 }
 
+impl GuildMembers {
+    pub fn rank(&self, rank: GuildRank) -> &Map<String, GuildPlayerInfo> {
+        match rank {
+            GuildRank::Owner => &self.owner,
+            GuildRank::Chief => &self.chief,
+            GuildRank::Strategist => &self.strategist,
+            GuildRank::Captain => &self.captain,
+            GuildRank::Recruiter => &self.recruiter,
+            GuildRank::Recruit => &self.recruit,
+        }
+    }
+
+    /// Every member with their key, which is the username or uuid depending on the [`Identifier`] of the request.
+    pub fn entries(&self) -> impl Iterator<Item = (GuildRank, &String, &GuildPlayerInfo)> {
+        GuildRank::ALL
+            .into_iter()
+            .rev()
+            .flat_map(|rank| self.rank(rank).iter().map(move |(k, v)| (rank, k, v)))
+    }
+
+    /// Every member, from the highest to the lowest rank.
+    pub fn iter(&self) -> impl Iterator<Item = (GuildRank, &GuildPlayerInfo)> {
+        self.entries().map(|(rank, _, info)| (rank, info))
+    }
+
+    pub fn online(&self) -> impl Iterator<Item = (GuildRank, &GuildPlayerInfo)> {
+        self.iter().filter(|(_, info)| info.online)
+    }
+
+    pub fn by_uuid(&self, uuid: PlayerUuid) -> Option<(GuildRank, &GuildPlayerInfo)> {
+        self.entries()
+            .find(|(_, key, info)| {
+                info.uuid == Some(uuid) || key.parse::<PlayerUuid>().is_ok_and(|k| k == uuid)
+            })
+            .map(|(rank, _, info)| (rank, info))
+    }
+
+    /// Usernames are compared case insensitively, like minecraft does.
+    pub fn by_username(&self, username: &str) -> Option<(GuildRank, &GuildPlayerInfo)> {
+        self.entries()
+            .find(|(_, key, info)| {
+                info.username
+                    .as_deref()
+                    .unwrap_or(key)
+                    .eq_ignore_ascii_case(username)
+            })
+            .map(|(rank, _, info)| (rank, info))
+    }
+
+    /// Every member, sorted from the highest to the lowest `contributed` xp.
+    pub fn by_contribution(&self) -> Vec<(GuildRank, &GuildPlayerInfo)> {
+        let mut members: Vec<_> = self.iter().collect();
+        members.sort_by_key(|(_, info)| std::cmp::Reverse(info.contributed));
+        members
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct GuildPlayerInfo {
     // one of these two is always none depending on the api call
     // could maybe be expressed a bit better
     pub username: Option<String>,
-    pub uuid: Option<String>,
+    pub uuid: Option<PlayerUuid>,
     pub online: bool,
     pub server: Option<World>,
     pub contributed: u64,
@@ -48,10 +169,163 @@ pub struct GuildPlayerInfo {
     pub joined: String,
 }
 
+#[cfg(feature = "chrono")]
+impl GuildPlayerInfo {
+    pub fn joined_at(&self) -> Result<chrono::DateTime<chrono::Utc>, InvalidTimestamp> {
+        timestamp::parse(&self.joined)
+    }
+
+    /// How long the player has been in the guild.
+    pub fn member_for(&self) -> Result<chrono::TimeDelta, InvalidTimestamp> {
+        Ok(chrono::Utc::now() - self.joined_at()?)
+    }
+}
+
+/// Something that happened to a member between two snapshots of a guild.
+///
+/// `member` is the key of the member in [`GuildMembers`], so a username or uuid depending on the [`Identifier`] used.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum GuildEvent {
+    Joined {
+        member: String,
+        rank: GuildRank,
+    },
+    Left {
+        member: String,
+        rank: GuildRank,
+    },
+    RankChanged {
+        member: String,
+        from: GuildRank,
+        to: GuildRank,
+    },
+    ContributionDelta {
+        member: String,
+        delta: i64,
+        contributed: u64,
+    },
+    CameOnline {
+        member: String,
+        server: Option<World>,
+    },
+    WentOffline {
+        member: String,
+    },
+}
+
+impl GuildEvent {
+    pub fn member(&self) -> &str {
+        match self {
+            GuildEvent::Joined { member, .. }
+            | GuildEvent::Left { member, .. }
+            | GuildEvent::RankChanged { member, .. }
+            | GuildEvent::ContributionDelta { member, .. }
+            | GuildEvent::CameOnline { member, .. }
+            | GuildEvent::WentOffline { member } => member,
+        }
+    }
+
+    pub fn is_promotion(&self) -> bool {
+        matches!(self, GuildEvent::RankChanged { from, to, .. } if to > from)
+    }
+
+    pub fn is_demotion(&self) -> bool {
+        matches!(self, GuildEvent::RankChanged { from, to, .. } if to < from)
+    }
+}
+
+/// The changes to the members of a guild between two snapshots, both requested with the same [`Identifier`].
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct GuildDiff {
+    pub guild: GuildUuid,
+    /// sorted by member, in the order joined/left, rank, contribution and online status
+    pub events: Vec<GuildEvent>,
+}
+
+impl GuildDiff {
+    pub fn between(old: &Guild, new: &Guild) -> Self {
+        let roster = |guild: &'_ Guild| -> BTreeMap<String, (GuildRank, GuildPlayerInfo)> {
+            guild
+                .members
+                .entries()
+                .map(|(rank, key, info)| (key.clone(), (rank, info.clone())))
+                .collect()
+        };
+        let (old_roster, new_roster) = (roster(old), roster(new));
+        let mut members: Vec<&String> = old_roster.keys().chain(new_roster.keys()).collect();
+        members.sort();
+        members.dedup();
+
+        let mut events = Vec::new();
+        for key in members {
+            let member = key.clone();
+            let (before, after) = match (old_roster.get(key), new_roster.get(key)) {
+                (Some(before), Some(after)) => (before, after),
+                (None, Some((rank, _))) => {
+                    events.push(GuildEvent::Joined {
+                        member,
+                        rank: *rank,
+                    });
+                    continue;
+                }
+                (Some((rank, _)), None) => {
+                    events.push(GuildEvent::Left {
+                        member,
+                        rank: *rank,
+                    });
+                    continue;
+                }
+                (None, None) => continue,
+            };
+            if before.0 != after.0 {
+                events.push(GuildEvent::RankChanged {
+                    member: member.clone(),
+                    from: before.0,
+                    to: after.0,
+                });
+            }
+            let delta = after.1.contributed as i64 - before.1.contributed as i64;
+            if delta != 0 {
+                events.push(GuildEvent::ContributionDelta {
+                    member: member.clone(),
+                    delta,
+                    contributed: after.1.contributed,
+                });
+            }
+            match (before.1.online, after.1.online) {
+                (false, true) => events.push(GuildEvent::CameOnline {
+                    member,
+                    server: after.1.server,
+                }),
+                (true, false) => events.push(GuildEvent::WentOffline { member }),
+                _ => {}
+            }
+        }
+        GuildDiff {
+            guild: new.uuid,
+            events,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    /// The xp every member contributed between the snapshots, members that joined or left are not included.
+    pub fn contributions(&self) -> impl Iterator<Item = (&str, i64)> {
+        self.events.iter().filter_map(|event| match event {
+            GuildEvent::ContributionDelta { member, delta, .. } => Some((member.as_str(), *delta)),
+            _ => None,
+        })
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct Banner {
-    pub base: String,
+    pub base: BannerColour,
     pub tier: u64,
     pub structure: String,
     pub layers: Vec<BannerLayer>,
@@ -60,8 +334,8 @@ pub struct Banner {
 #[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct BannerLayer {
-    pub colour: String,
-    pub pattern: String,
+    pub colour: BannerColour,
+    pub pattern: BannerPattern,
 }
 
 #[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
@@ -74,7 +348,7 @@ pub struct SeasonRank {
 #[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct ShortGuildDescription {
-    pub uuid: String,
+    pub uuid: GuildUuid,
     #[serde(default, deserialize_with = "deserialize_with_default")]
     pub prefix: String,
 }
@@ -82,12 +356,30 @@ pub struct ShortGuildDescription {
 #[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct GuildDescription {
-    pub uuid: String,
+    pub uuid: GuildUuid,
     pub name: String,
     #[serde(default, deserialize_with = "deserialize_with_default")]
     pub prefix: String,
 }
 
+/// One of the guilds the api returns when a name belongs to multiple guilds.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct GuildCandidate {
+    /// the api uses this as the key of the candidate
+    pub uuid: GuildUuid,
+    pub name: String,
+    #[serde(default, deserialize_with = "deserialize_with_default")]
+    pub prefix: String,
+}
+
+impl WynnApiError {
+    /// The guilds the api returned with [`WynnApiError::MultipleChoices`], guilds that can't be decoded are left out.
+    pub fn guild_candidates(&self) -> Option<Vec<GuildCandidate>> {
+        self.candidates()
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct Territory {
@@ -96,6 +388,18 @@ pub struct Territory {
     pub location: TerritoryLocation,
 }
 
+#[cfg(feature = "chrono")]
+impl Territory {
+    pub fn acquired_at(&self) -> Result<chrono::DateTime<chrono::Utc>, InvalidTimestamp> {
+        timestamp::parse(&self.acquired)
+    }
+
+    /// How long the current guild has held the territory.
+    pub fn held_for(&self) -> Result<chrono::TimeDelta, InvalidTimestamp> {
+        Ok(chrono::Utc::now() - self.acquired_at()?)
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct TerritoryLocation {
@@ -103,41 +407,229 @@ pub struct TerritoryLocation {
     pub end: (i64, i64),
 }
 
+impl TerritoryLocation {
+    /// The territory as a rectangle, `start` and `end` can be any two opposite corners.
+    pub fn rect(&self) -> Rect {
+        Rect::new(self.start, self.end)
+    }
+
+    pub fn area(&self) -> i64 {
+        self.rect().area()
+    }
+
+    pub fn centre(&self) -> (f64, f64) {
+        self.rect().centre()
+    }
+
+    pub fn contains(&self, x: i64, z: i64) -> bool {
+        self.rect().contains(x, z)
+    }
+}
+
+impl WynnClient {
+    pub async fn guild_by_name(
+        &self,
+        guild_name: &str,
+        identifier: Identifier,
+    ) -> Result<Guild, WynnApiError> {
+        self.api_request(&format!("/guild/{guild_name}?identifier={identifier}"))
+            .await
+    }
+
+    /// Like [`WynnClient::guild_by_name`], but when the name belongs to multiple guilds
+    /// `resolver` picks one of them, which is then requested by uuid.
+    ///
+    /// If `resolver` returns `None` the [`WynnApiError::MultipleChoices`] error is returned.
+    pub async fn guild_by_name_resolved(
+        &self,
+        guild_name: &str,
+        identifier: Identifier,
+        resolver: impl FnOnce(Vec<GuildCandidate>) -> Option<GuildCandidate>,
+    ) -> Result<Guild, WynnApiError> {
+        match self.guild_by_name(guild_name, identifier).await {
+            Err(e @ WynnApiError::MultipleChoices(_)) => {
+                match e.guild_candidates().and_then(resolver) {
+                    Some(candidate) => self.guild_by_uuid(candidate.uuid, identifier).await,
+                    None => Err(e),
+                }
+            }
+            result => result,
+        }
+    }
+
+    pub async fn guild_by_uuid(
+        &self,
+        guild_uuid: GuildUuid,
+        identifier: Identifier,
+    ) -> Result<Guild, WynnApiError> {
+        self.api_request(&format!("/guild/uuid/{guild_uuid}?identifier={identifier}"))
+            .await
+    }
+
+    pub async fn guild_by_prefix(
+        &self,
+        guild_prefix: &str,
+        identifier: Identifier,
+    ) -> Result<Guild, WynnApiError> {
+        self.api_request(&format!(
+            "/guild/prefix/{guild_prefix}?identifier={identifier}"
+        ))
+        .await
+    }
+
+    pub async fn guild_list(&self) -> Result<Map<String, ShortGuildDescription>, WynnApiError> {
+        self.api_request("/guild/list/guild").await
+    }
+
+    pub async fn guild_teritories(&self) -> Result<Map<String, Territory>, WynnApiError> {
+        self.api_request("/guild/list/territory").await
+    }
+}
+
 pub async fn guild_by_name(
     guild_name: &str,
     identifier: Identifier,
 ) -> Result<Guild, WynnApiError> {
-    api_request(&format!(
-        "{API_LOCATION}/guild/{guild_name}?identifier={identifier}"
-    ))
-    .await
+    WynnClient::global()
+        .guild_by_name(guild_name, identifier)
+        .await
+}
+
+pub async fn guild_by_name_resolved(
+    guild_name: &str,
+    identifier: Identifier,
+    resolver: impl FnOnce(Vec<GuildCandidate>) -> Option<GuildCandidate>,
+) -> Result<Guild, WynnApiError> {
+    WynnClient::global()
+        .guild_by_name_resolved(guild_name, identifier, resolver)
+        .await
+}
+
+pub async fn guild_by_uuid(
+    guild_uuid: GuildUuid,
+    identifier: Identifier,
+) -> Result<Guild, WynnApiError> {
+    WynnClient::global()
+        .guild_by_uuid(guild_uuid, identifier)
+        .await
 }
 
 pub async fn guild_by_prefix(
     guild_prefix: &str,
     identifier: Identifier,
 ) -> Result<Guild, WynnApiError> {
-    api_request(&format!(
-        "{API_LOCATION}/guild/prefix/{guild_prefix}?identifier={identifier}"
-    ))
-    .await
+    WynnClient::global()
+        .guild_by_prefix(guild_prefix, identifier)
+        .await
 }
 
 pub async fn guild_list() -> Result<Map<String, ShortGuildDescription>, WynnApiError> {
-    api_request(&format!("{API_LOCATION}/guild/list/guild")).await
+    WynnClient::global().guild_list().await
 }
 
 pub async fn guild_teritories() -> Result<Map<String, Territory>, WynnApiError> {
-    api_request(&format!("{API_LOCATION}/guild/list/territory")).await
+    WynnClient::global().guild_teritories().await
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use reqwest::StatusCode;
+    use crate::mock;
     use std::fmt::Debug;
     use tokio;
 
+    #[test]
+    fn roster() {
+        let guild: Guild = serde_json::from_str(mock::GUILD).unwrap();
+        let members = &guild.members;
+        assert_eq!(members.iter().count(), 4);
+        assert_eq!(members.iter().next().unwrap().0, GuildRank::Owner);
+        assert_eq!(members.online().count(), 2);
+
+        let (rank, info) = members.by_username("BOB").unwrap();
+        assert_eq!(rank, GuildRank::Captain);
+        let uuid = info.uuid.unwrap();
+        assert_eq!(members.by_uuid(uuid).unwrap().0, GuildRank::Captain);
+        assert!(members.by_username("nobody").is_none());
+
+        let contributions: Vec<u64> = members
+            .by_contribution()
+            .iter()
+            .map(|(_, info)| info.contributed)
+            .collect();
+        assert_eq!(contributions, [5000, 1200, 300, 0]);
+    }
+
+    #[test]
+    fn diff() {
+        let old: Guild = serde_json::from_str(mock::GUILD).unwrap();
+        let mut new = old.clone();
+        let members = &mut new.members;
+        members.recruit.clear();
+        let mut eve = members.recruiter["Carol"].clone();
+        eve.contributed = 0;
+        members.recruit.insert("Eve".to_owned(), eve);
+        let mut bob = members.captain.remove("Bob").unwrap();
+        bob.online = true;
+        bob.server = Some(World(3));
+        members.strategist.insert("Bob".to_owned(), bob);
+        members.owner.get_mut("Alice").unwrap().contributed += 500;
+        members.recruiter.get_mut("Carol").unwrap().online = false;
+
+        let diff = GuildDiff::between(&old, &new);
+        assert_eq!(
+            diff.events,
+            [
+                GuildEvent::ContributionDelta {
+                    member: "Alice".to_owned(),
+                    delta: 500,
+                    contributed: 5500
+                },
+                GuildEvent::RankChanged {
+                    member: "Bob".to_owned(),
+                    from: GuildRank::Captain,
+                    to: GuildRank::Strategist
+                },
+                GuildEvent::CameOnline {
+                    member: "Bob".to_owned(),
+                    server: Some(World(3))
+                },
+                GuildEvent::WentOffline {
+                    member: "Carol".to_owned()
+                },
+                GuildEvent::Left {
+                    member: "Dave".to_owned(),
+                    rank: GuildRank::Recruit
+                },
+                GuildEvent::Joined {
+                    member: "Eve".to_owned(),
+                    rank: GuildRank::Recruit
+                },
+            ]
+        );
+        assert!(diff.events[1].is_promotion());
+        assert_eq!(diff.contributions().collect::<Vec<_>>(), [("Alice", 500)]);
+        assert!(GuildDiff::between(&old, &old).is_empty());
+
+        let json = serde_json::to_string(&diff).unwrap();
+        assert_eq!(serde_json::from_str::<GuildDiff>(&json).unwrap(), diff);
+    }
+
+    #[test]
+    fn ranks() {
+        assert!(GuildRank::Owner > GuildRank::Chief);
+        assert!(GuildRank::Recruit < GuildRank::Recruiter);
+        assert_eq!(GuildRank::from_stars("**"), Some(GuildRank::Captain));
+        assert_eq!(GuildRank::from_stars(""), Some(GuildRank::Recruit));
+        assert_eq!(GuildRank::from_stars("★★★★★"), Some(GuildRank::Owner));
+        assert_eq!(GuildRank::from_stars("******"), None);
+        assert_eq!(GuildRank::Chief.stars(), 4);
+        assert_eq!(
+            serde_json::from_str::<GuildRank>(r#""STRATEGIST""#).unwrap(),
+            GuildRank::Strategist
+        );
+    }
+
     #[tokio::test]
     async fn guild_stats() {
         fn verify<T: Debug>(t: &Result<T, WynnApiError>) {
@@ -145,11 +637,7 @@ mod tests {
                 Ok(x) => {
                     dbg!(x);
                 }
-                Err(WynnApiError::ConnectionError(e)) => {
-                    if e.status() != Some(StatusCode::from_u16(500).unwrap()) {
-                        panic!("{}", e)
-                    }
-                }
+                Err(WynnApiError::ServerError { .. }) => {}
                 e => {
                     panic!("{:?}", e);
                 }
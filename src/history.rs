@@ -0,0 +1,404 @@
+use crate::{
+    guild::{GuildDescription, Territory},
+    store::{write_atomic, StoreError},
+    timestamp,
+    uuid::GuildUuid,
+    Map,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fmt::Debug,
+    fs,
+    io::{self, Write},
+    path::PathBuf,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+/// A period during which a guild held a territory.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct OwnershipInterval {
+    pub territory: String,
+    pub guild: GuildDescription,
+    pub start: SystemTime,
+    /// `None` while the guild still holds the territory
+    pub end: Option<SystemTime>,
+    /// the guild that held the territory before, `None` if it wasn't known yet
+    pub previous: Option<GuildUuid>,
+}
+
+impl OwnershipInterval {
+    /// Whether the territory was taken from another guild at the start of this interval.
+    pub fn is_capture(&self) -> bool {
+        self.previous.is_some()
+    }
+
+    /// How long the guild held the territory, up to `now` if it still does.
+    pub fn duration(&self, now: SystemTime) -> Duration {
+        self.end
+            .unwrap_or(now)
+            .duration_since(self.start)
+            .unwrap_or_default()
+    }
+}
+
+/// Persistent storage for a [`TerritoryHistory`].
+pub trait HistoryStore: Debug + Send + Sync {
+    fn load(&self) -> Result<Vec<OwnershipInterval>, StoreError>;
+    fn save(&self, intervals: &[OwnershipInterval]) -> Result<(), StoreError>;
+}
+
+/// Stores the whole history in a single json file.
+#[derive(Debug, Clone)]
+pub struct JsonHistoryStore {
+    path: PathBuf,
+}
+
+impl JsonHistoryStore {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+}
+
+impl HistoryStore for JsonHistoryStore {
+    fn load(&self) -> Result<Vec<OwnershipInterval>, StoreError> {
+        match fs::read_to_string(&self.path) {
+            Ok(text) => Ok(serde_json::from_str(&text)?),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Vec::new()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn save(&self, intervals: &[OwnershipInterval]) -> Result<(), StoreError> {
+        write_atomic(&self.path, serde_json::to_string(intervals)?)?;
+        Ok(())
+    }
+}
+
+/// Who held which territory when, built from successive results of
+/// [`guild_teritories`](crate::WynnClient::guild_teritories).
+#[derive(Debug, Default)]
+pub struct TerritoryHistory {
+    /// sorted by start per territory
+    intervals: Vec<OwnershipInterval>,
+    /// the index of the interval that hasn't ended yet per territory
+    open: Map<String, usize>,
+    store: Option<Box<dyn HistoryStore>>,
+    /// whether there are changes that aren't in the store yet
+    unsaved: bool,
+}
+
+impl TerritoryHistory {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Loads the history saved in `store`, [`save`](Self::save) writes it back.
+    pub fn with_store(store: impl HistoryStore + 'static) -> Result<Self, StoreError> {
+        let intervals = store.load()?;
+        let open = intervals
+            .iter()
+            .enumerate()
+            .filter(|(_, i)| i.end.is_none())
+            .map(|(index, i)| (i.territory.clone(), index))
+            .collect();
+        Ok(Self {
+            intervals,
+            open,
+            store: Some(Box::new(store)),
+            unsaved: false,
+        })
+    }
+
+    /// Writes the history to its store if anything changed since it was loaded or last saved.
+    ///
+    /// Ingesting doesn't save, so a caller that ingests often can choose to save less often.
+    pub fn save(&mut self) -> Result<(), StoreError> {
+        if let Some(store) = self.store.as_ref().filter(|_| self.unsaved) {
+            store.save(&self.intervals)?;
+            self.unsaved = false;
+        }
+        Ok(())
+    }
+
+    /// Whether there are ingested changes that [`save`](Self::save) hasn't written yet.
+    pub fn has_unsaved_changes(&self) -> bool {
+        self.unsaved
+    }
+
+    /// Records the owners in `territories`, a snapshot taken at `at`.
+    ///
+    /// A new owner is assumed to have taken the territory at its `acquired` date, or at `at` if that is unknown.
+    /// `territories` has to be the whole map, a territory that isn't in it (like a renamed or removed one)
+    /// has no owner from `at` on.
+    /// Returns the amount of captures in the snapshot.
+    pub fn ingest(&mut self, territories: &Map<String, Territory>, at: SystemTime) -> usize {
+        let gone: Vec<_> = self
+            .open
+            .keys()
+            .filter(|name| !territories.contains_key(*name))
+            .cloned()
+            .collect();
+        for name in gone {
+            if let Some(index) = self.open.remove(&name) {
+                let interval = &mut self.intervals[index];
+                interval.end = Some(at.max(interval.start));
+                self.unsaved = true;
+            }
+        }
+
+        let mut captures = 0;
+        // sorted so the intervals are always added in the same order
+        let territories: BTreeMap<_, _> = territories.iter().collect();
+        for (name, territory) in territories {
+            let start = timestamp::to_system_time(&territory.acquired).unwrap_or(at);
+            let current = self.open.get(name).map(|&index| &mut self.intervals[index]);
+            let previous = match current {
+                Some(current) if current.guild.uuid == territory.guild.uuid => continue,
+                Some(current) => {
+                    current.end = Some(start.max(current.start));
+                    captures += 1;
+                    Some(current.guild.uuid)
+                }
+                None => None,
+            };
+            self.open.insert(name.clone(), self.intervals.len());
+            self.intervals.push(OwnershipInterval {
+                territory: name.clone(),
+                guild: territory.guild.clone(),
+                start,
+                end: None,
+                previous,
+            });
+            self.unsaved = true;
+        }
+        captures
+    }
+
+    pub fn intervals(&self) -> &[OwnershipInterval] {
+        &self.intervals
+    }
+
+    /// Every interval of `territory`, from old to new.
+    pub fn territory(&self, territory: &str) -> impl Iterator<Item = &OwnershipInterval> {
+        let territory = territory.to_owned();
+        self.intervals
+            .iter()
+            .filter(move |i| i.territory == territory)
+    }
+
+    pub fn current_owner(&self, territory: &str) -> Option<&GuildDescription> {
+        self.open.get(territory).map(|&i| &self.intervals[i].guild)
+    }
+
+    /// How long `guild` has held `territory` without losing it, `None` if it doesn't hold it right now.
+    pub fn held_for(&self, guild: GuildUuid, territory: &str, now: SystemTime) -> Option<Duration> {
+        let current = &self.intervals[*self.open.get(territory)?];
+        (current.guild.uuid == guild).then(|| current.duration(now))
+    }
+
+    /// How long `guild` has held `territory` in total over all recorded intervals.
+    pub fn total_held(&self, guild: GuildUuid, territory: &str, now: SystemTime) -> Duration {
+        self.territory(territory)
+            .filter(|i| i.guild.uuid == guild)
+            .map(|i| i.duration(now))
+            .sum()
+    }
+
+    /// Every capture from `since` on.
+    pub fn captures_since(&self, since: SystemTime) -> impl Iterator<Item = &OwnershipInterval> {
+        self.intervals
+            .iter()
+            .filter(move |i| i.is_capture() && i.start >= since)
+    }
+
+    /// The amount of captures in the `window` before `now`, like the last 24 hours.
+    pub fn capture_count(&self, window: Duration, now: SystemTime) -> usize {
+        let since = now.checked_sub(window).unwrap_or(UNIX_EPOCH);
+        self.captures_since(since).count()
+    }
+
+    /// The `n` guilds that captured the most territories since `since`, with the amount of captures.
+    pub fn top_aggressors(&self, since: SystemTime, n: usize) -> Vec<(&GuildDescription, usize)> {
+        let mut counts: BTreeMap<GuildUuid, (&GuildDescription, usize)> = BTreeMap::new();
+        for capture in self.captures_since(since) {
+            counts
+                .entry(capture.guild.uuid)
+                .or_insert((&capture.guild, 0))
+                .1 += 1;
+        }
+        let mut counts: Vec<_> = counts.into_values().collect();
+        // stable sort, so equal counts stay sorted by uuid
+        counts.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+        counts.truncate(n);
+        counts
+    }
+
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string_pretty(&self.intervals)
+    }
+
+    /// Writes every interval as a csv row, times are in seconds since the unix epoch.
+    pub fn write_csv(&self, mut writer: impl Write) -> io::Result<()> {
+        fn field(s: &str) -> String {
+            if s.contains([',', '"', '\n']) {
+                format!("\"{}\"", s.replace('"', "\"\""))
+            } else {
+                s.to_owned()
+            }
+        }
+        fn secs(time: SystemTime) -> u64 {
+            time.duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs()
+        }
+
+        writeln!(
+            writer,
+            "territory,guild_uuid,guild_name,guild_prefix,start,end,previous"
+        )?;
+        for i in &self.intervals {
+            writeln!(
+                writer,
+                "{},{},{},{},{},{},{}",
+                field(&i.territory),
+                i.guild.uuid,
+                field(&i.guild.name),
+                field(&i.guild.prefix),
+                secs(i.start),
+                i.end.map(|e| secs(e).to_string()).unwrap_or_default(),
+                i.previous.map(|p| p.to_string()).unwrap_or_default(),
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HOUR: Duration = Duration::from_secs(3600);
+
+    fn snapshot(owners: &[(&str, u128, &str)]) -> Map<String, Territory> {
+        owners
+            .iter()
+            .map(|&(territory, guild, acquired)| {
+                let json = serde_json::json!({
+                    "guild": {"uuid": GuildUuid::from_u128(guild), "name": format!("Guild {guild}"), "prefix": format!("G{guild}")},
+                    "acquired": acquired,
+                    "location": {"start": [0, 0], "end": [10, 10]}
+                });
+                (territory.to_owned(), serde_json::from_value(json).unwrap())
+            })
+            .collect()
+    }
+
+    fn time(acquired: &str) -> SystemTime {
+        timestamp::to_system_time(acquired).unwrap()
+    }
+
+    #[test]
+    fn ownership() {
+        let mut history = TerritoryHistory::new();
+        let day1 = "2024-09-01T00:00:00.000Z";
+        let first = snapshot(&[
+            ("Ragni", 1, day1),
+            ("Detlas", 1, day1),
+            ("Nemract", 2, day1),
+        ]);
+        assert_eq!(history.ingest(&first, time(day1)), 0);
+
+        let day2 = "2024-09-02T00:00:00.000Z";
+        let second = snapshot(&[
+            ("Ragni", 2, day2),
+            ("Detlas", 3, day2),
+            ("Nemract", 2, day1),
+        ]);
+        assert_eq!(history.ingest(&second, time(day2)), 2);
+        let third = snapshot(&[
+            ("Ragni", 2, day2),
+            ("Detlas", 2, day2),
+            ("Nemract", 2, day1),
+        ]);
+        assert_eq!(history.ingest(&third, time(day2) + HOUR), 1);
+
+        let now = time(day2) + 2 * HOUR;
+        let (one, two) = (GuildUuid::from_u128(1), GuildUuid::from_u128(2));
+        assert_eq!(history.held_for(two, "Ragni", now), Some(2 * HOUR));
+        assert_eq!(history.held_for(one, "Ragni", now), None);
+        assert_eq!(history.total_held(one, "Ragni", now), 24 * HOUR);
+        assert_eq!(history.current_owner("Detlas").unwrap().uuid, two);
+        assert_eq!(history.capture_count(24 * HOUR, now), 3);
+        assert_eq!(history.capture_count(HOUR, now), 0);
+
+        let aggressors = history.top_aggressors(time(day1), 5);
+        let aggressors: Vec<_> = aggressors.iter().map(|(g, n)| (g.uuid, *n)).collect();
+        assert_eq!(aggressors, [(two, 2), (GuildUuid::from_u128(3), 1)]);
+    }
+
+    #[test]
+    fn territory_removed() {
+        let mut history = TerritoryHistory::new();
+        let day1 = "2024-09-01T00:00:00.000Z";
+        history.ingest(
+            &snapshot(&[("Ragni", 1, day1), ("Old Ragni", 1, day1)]),
+            time(day1),
+        );
+        // `Old Ragni` is renamed or removed
+        let removed_at = time(day1) + HOUR;
+        assert_eq!(
+            history.ingest(&snapshot(&[("Ragni", 1, day1)]), removed_at),
+            0
+        );
+        assert!(history.has_unsaved_changes());
+
+        let (one, now) = (GuildUuid::from_u128(1), removed_at + 10 * HOUR);
+        assert_eq!(history.current_owner("Old Ragni"), None);
+        assert_eq!(history.held_for(one, "Old Ragni", now), None);
+        assert_eq!(history.total_held(one, "Old Ragni", now), HOUR);
+        assert_eq!(history.held_for(one, "Ragni", now), Some(11 * HOUR));
+    }
+
+    #[test]
+    fn persistence() {
+        let path =
+            std::env::temp_dir().join(format!("wynncraft_api_history_{}.json", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let day1 = "2024-09-01T00:00:00.000Z";
+        let mut history = TerritoryHistory::with_store(JsonHistoryStore::new(&path)).unwrap();
+        history.ingest(&snapshot(&[("Ragni, the city", 1, day1)]), time(day1));
+        // nothing is written until the caller saves
+        assert!(!path.exists());
+        assert!(history.has_unsaved_changes());
+        history.save().unwrap();
+        assert!(!history.has_unsaved_changes());
+
+        let mut loaded = TerritoryHistory::with_store(JsonHistoryStore::new(&path)).unwrap();
+        assert_eq!(loaded.intervals(), history.intervals());
+        assert_eq!(
+            loaded.current_owner("Ragni, the city").unwrap().name,
+            "Guild 1"
+        );
+        // the owner didn't change, so there is nothing to save
+        assert_eq!(
+            loaded.ingest(&snapshot(&[("Ragni, the city", 1, day1)]), time(day1)),
+            0
+        );
+        assert!(!loaded.has_unsaved_changes());
+        fs::remove_file(path).unwrap();
+
+        let mut csv = Vec::new();
+        loaded.write_csv(&mut csv).unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+        assert_eq!(
+            csv.lines().nth(1).unwrap(),
+            "\"Ragni, the city\",00000000-0000-0000-0000-000000000001,Guild 1,G1,1725148800,,"
+        );
+        let json: Vec<OwnershipInterval> =
+            serde_json::from_str(&loaded.to_json().unwrap()).unwrap();
+        assert_eq!(json, history.intervals());
+    }
+}
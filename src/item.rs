@@ -1,6 +1,6 @@
 use crate::{
-    api_request, classes::Class, deserialize_with_default, player::Icon, post_api_request, Map,
-    Set, WynnApiError, API_LOCATION,
+    classes::Class, deserialize_unknown, deserialize_with_default, is_lenient, player::Icon, Map,
+    Set, WynnApiError, WynnClient,
 };
 use serde::{Deserialize, Deserializer, Serialize};
 use serde_json::Value;
@@ -418,6 +418,9 @@ pub enum DropType {
     Dungeon,
     Challenge,
     EventMerchant,
+    /// a value this crate doesn't know about, only decoded in [lenient](crate::with_lenient) mode
+    #[serde(untagged)]
+    Unknown(String),
 }
 
 impl<'de> Deserialize<'de> for DropType {
@@ -438,6 +441,7 @@ impl<'de> Deserialize<'de> for DropType {
                 "dungeonMerchant" => DropType::DungeonMerchant,
                 "dungeon" => DropType::Dungeon,
                 "challenge" => DropType::Challenge,
+                _ if is_lenient() => DropType::Unknown(s),
                 _ => Err(serde::de::Error::custom("not a dropType"))?,
             },
             Value::Array(_) => DropType::EventMerchant,
@@ -719,7 +723,7 @@ pub enum Profession {
     Woodcutting,
 }
 
-#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Copy, PartialOrd, Eq, Ord, Hash)]
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, PartialOrd, Eq, Ord, Hash)]
 #[serde(rename_all = "camelCase")]
 pub enum Identification {
     #[serde(rename = "rawStrength")]
@@ -911,9 +915,12 @@ pub enum Identification {
     GatherXpBonus,
     #[serde(rename = "gatherSpeed")]
     GatherSpeed,
+    /// a value this crate doesn't know about, only decoded in [lenient](crate::with_lenient) mode
+    #[serde(untagged, deserialize_with = "deserialize_unknown")]
+    Unknown(String),
 }
 
-#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Copy, PartialOrd, Eq, Ord, Hash)]
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, PartialOrd, Eq, Ord, Hash)]
 pub enum MajorId {
     #[serde(rename = "Alter Ego")]
     AlterEgo,
@@ -1109,27 +1116,54 @@ pub enum MajorId {
     TwistingThreads,
     #[serde(rename = "Windsurf")]
     Windsurf,
+    /// a value this crate doesn't know about, only decoded in [lenient](crate::with_lenient) mode
+    #[serde(untagged, deserialize_with = "deserialize_unknown")]
+    Unknown(String),
+}
+
+impl WynnClient {
+    pub async fn item_database(&self, page: u64) -> Result<ItemResult, WynnApiError> {
+        self.api_request(&format!("/item/database?page={page}"))
+            .await
+    }
+
+    pub async fn item_database_full(&self) -> Result<Map<String, Item>, WynnApiError> {
+        self.api_request("/item/database?fullResult").await
+    }
+
+    pub async fn search_item(&self, query: &ItemQuery) -> Result<ItemResult, WynnApiError> {
+        self.post_api_request("/item/search", query).await
+    }
+
+    pub async fn search_item_full(
+        &self,
+        query: &ItemQuery,
+    ) -> Result<Map<String, Item>, WynnApiError> {
+        self.post_api_request("/item/search?fullResult", query)
+            .await
+    }
 }
 
 pub async fn item_database(page: u64) -> Result<ItemResult, WynnApiError> {
-    api_request(&format!("{API_LOCATION}/item/database?page={page}")).await
+    WynnClient::global().item_database(page).await
 }
 
 pub async fn item_database_full() -> Result<Map<String, Item>, WynnApiError> {
-    api_request(&format!("{API_LOCATION}/item/database?fullResult")).await
+    WynnClient::global().item_database_full().await
 }
 
 pub async fn search_item(query: &ItemQuery) -> Result<ItemResult, WynnApiError> {
-    post_api_request(&format!("{API_LOCATION}/item/search"), query).await
+    WynnClient::global().search_item(query).await
 }
 
 pub async fn search_item_full(query: &ItemQuery) -> Result<Map<String, Item>, WynnApiError> {
-    post_api_request(&format!("{API_LOCATION}/item/search?fullResult"), query).await
+    WynnClient::global().search_item_full(query).await
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::mock::{MockResponse, MockServer};
 
     #[tokio::test]
     async fn items() {
@@ -1150,4 +1184,25 @@ mod tests {
             assert_eq!(item.results, item2.unwrap());
         }
     }
+
+    #[tokio::test]
+    async fn search_with_client() {
+        let server = MockServer::start(|request| {
+            assert_eq!(request.method, "POST");
+            assert_eq!(request.path, "/item/search?fullResult");
+            let query: ItemQuery = serde_json::from_str(&request.body).unwrap();
+            assert_eq!(query.query.as_deref(), Some("photon"));
+            MockResponse::json("{}")
+        })
+        .await;
+        let client = WynnClient::builder()
+            .base_url(server.url())
+            .build()
+            .unwrap();
+        let items = client
+            .search_item_full(&ItemQuery::with_query("photon"))
+            .await
+            .unwrap();
+        assert!(items.is_empty());
+    }
 }
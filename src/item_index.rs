@@ -0,0 +1,408 @@
+//! Searching the item database offline.
+//!
+//! [`ItemIndex`] keeps the whole item database in memory and answers an [`ItemQuery`], the query of
+//! [`search_item_full`](crate::item::search_item_full), without a request per search.
+//! The results aren't checked against the search endpoint and can differ from it.
+
+use crate::{
+    item::{
+        AttackSpeed, Identification, Item, ItemQuery, ItemType, ItemTypeInfo, MajorId,
+        OtherItemType, Profession, Rarity, ToolType,
+    },
+    Map, Set, WynnApiError, WynnClient,
+};
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// The item database with indexes on every field an [`ItemQuery`] can filter on.
+///
+/// An item has to match every filter of the query that is set,
+/// for tiers, types, attack speeds and professions it has to match one of the values,
+/// for identifications and major ids it needs all of them.
+#[derive(Debug, Clone, Default)]
+pub struct ItemIndex {
+    /// sorted by name
+    items: Vec<(String, Item)>,
+    /// lowercase names for the name query
+    names: Vec<String>,
+    by_level: BTreeMap<u8, Vec<usize>>,
+    by_type: BTreeMap<ItemType, Vec<usize>>,
+    by_tier: BTreeMap<Rarity, Vec<usize>>,
+    by_attack_speed: BTreeMap<AttackSpeed, Vec<usize>>,
+    by_profession: BTreeMap<Profession, Vec<usize>>,
+    by_identification: BTreeMap<Identification, Vec<usize>>,
+    by_major_id: BTreeMap<MajorId, Vec<usize>>,
+}
+
+impl ItemIndex {
+    /// Builds the index from the result of [`item_database_full`](crate::WynnClient::item_database_full).
+    pub fn new(items: Map<String, Item>) -> Self {
+        let mut items: Vec<_> = items.into_iter().collect();
+        items.sort_by(|a, b| a.0.cmp(&b.0));
+        let mut index = Self {
+            names: items.iter().map(|(name, _)| name.to_lowercase()).collect(),
+            ..Default::default()
+        };
+        for (i, (_, item)) in items.iter().enumerate() {
+            index
+                .by_level
+                .entry(item.required_level())
+                .or_default()
+                .push(i);
+            index.by_type.entry(item_type(item)).or_default().push(i);
+            index.by_tier.entry(tier(item)).or_default().push(i);
+            if let ItemTypeInfo::Weapon(weapon) = &item.item_type {
+                index
+                    .by_attack_speed
+                    .entry(weapon.attack_speed)
+                    .or_default()
+                    .push(i);
+            }
+            for profession in professions(item) {
+                index.by_profession.entry(profession).or_default().push(i);
+            }
+            for id in identifications(item) {
+                index
+                    .by_identification
+                    .entry(id.clone())
+                    .or_default()
+                    .push(i);
+            }
+            for major_id in major_ids(item) {
+                index
+                    .by_major_id
+                    .entry(major_id.clone())
+                    .or_default()
+                    .push(i);
+            }
+        }
+        index.items = items;
+        index
+    }
+
+    /// The items matching the query, sorted by name.
+    pub fn search(&self, query: &ItemQuery) -> Vec<(&str, &Item)> {
+        let (min, max) = query.level_range;
+        if min > max {
+            return Vec::new();
+        }
+        let mut matches: BTreeSet<usize> = self
+            .by_level
+            .range(min..=max)
+            .flat_map(|(_, items)| items.iter().copied())
+            .collect();
+
+        if !query.item_type.is_empty() {
+            matches = &matches & &any_of(&self.by_type, &query.item_type);
+        }
+        if !query.tier.is_empty() {
+            matches = &matches & &any_of(&self.by_tier, &query.tier);
+        }
+        if !query.attack_speed.is_empty() {
+            matches = &matches & &any_of(&self.by_attack_speed, &query.attack_speed);
+        }
+        if !query.professions.is_empty() {
+            matches = &matches & &any_of(&self.by_profession, &query.professions);
+        }
+        for id in &query.identifications {
+            let items = self
+                .by_identification
+                .get(id)
+                .map_or(&[][..], Vec::as_slice);
+            matches.retain(|i| items.binary_search(i).is_ok());
+        }
+        for major_id in &query.major_ids {
+            let items = self
+                .by_major_id
+                .get(major_id)
+                .map_or(&[][..], Vec::as_slice);
+            matches.retain(|i| items.binary_search(i).is_ok());
+        }
+        if let Some(name) = query.query.as_deref().filter(|name| !name.is_empty()) {
+            let name = name.to_lowercase();
+            matches.retain(|&i| self.names[i].contains(&name));
+        }
+
+        matches
+            .into_iter()
+            .map(|i| (self.items[i].0.as_str(), &self.items[i].1))
+            .collect()
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Item> {
+        self.items
+            .binary_search_by(|(item, _)| item.as_str().cmp(name))
+            .ok()
+            .map(|i| &self.items[i].1)
+    }
+
+    /// Every item, sorted by name.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Item)> {
+        self.items.iter().map(|(name, item)| (name.as_str(), item))
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+}
+
+/// the items with at least one of the keys
+fn any_of<K: Ord>(index: &BTreeMap<K, Vec<usize>>, keys: &Set<K>) -> BTreeSet<usize> {
+    keys.iter()
+        .filter_map(|key| index.get(key))
+        .flatten()
+        .copied()
+        .collect()
+}
+
+fn item_type(item: &Item) -> ItemType {
+    match &item.item_type {
+        ItemTypeInfo::Tool(x) => x.tool_type.into(),
+        ItemTypeInfo::Accessory(x) => x.accessory_type.into(),
+        ItemTypeInfo::Tome(x) => x.tome_type.into(),
+        ItemTypeInfo::Weapon(x) => x.weapon_type.into(),
+        ItemTypeInfo::Ingredient(_) => OtherItemType::Ingredient.into(),
+        ItemTypeInfo::Charm(_) => OtherItemType::Charm.into(),
+        ItemTypeInfo::Armour(x) => x.armour_type.into(),
+        ItemTypeInfo::Material(_) => OtherItemType::Material.into(),
+    }
+}
+
+fn tier(item: &Item) -> Rarity {
+    match &item.item_type {
+        ItemTypeInfo::Tool(x) => x.rarity.into(),
+        ItemTypeInfo::Accessory(x) => x.rarity.into(),
+        ItemTypeInfo::Tome(x) => x.rarity.into(),
+        ItemTypeInfo::Weapon(x) => x.rarity.into(),
+        ItemTypeInfo::Ingredient(x) => x.tier.into(),
+        ItemTypeInfo::Charm(x) => x.rarity.into(),
+        ItemTypeInfo::Armour(x) => x.rarity.into(),
+        ItemTypeInfo::Material(x) => x.tier.into(),
+    }
+}
+
+/// the crafting professions of an ingredient, or the gathering profession of a tool
+fn professions(item: &Item) -> Vec<Profession> {
+    match &item.item_type {
+        // skills are the uppercase names of the professions
+        ItemTypeInfo::Ingredient(x) => x
+            .requirements
+            .skills
+            .iter()
+            .filter_map(|skill| serde_json::from_value(Value::String(skill.to_lowercase())).ok())
+            .collect(),
+        ItemTypeInfo::Tool(x) => vec![match x.tool_type {
+            ToolType::Axe => Profession::Woodcutting,
+            ToolType::Rod => Profession::Fishing,
+            ToolType::Pickaxe => Profession::Mining,
+            ToolType::Scythe => Profession::Farming,
+        }],
+        _ => Vec::new(),
+    }
+}
+
+/// base stats and rolled identifications, without duplicates
+fn identifications(item: &Item) -> BTreeSet<&Identification> {
+    match &item.item_type {
+        ItemTypeInfo::Accessory(x) => x.base.keys().chain(x.identifications.keys()).collect(),
+        ItemTypeInfo::Tome(x) => x.identifications.keys().collect(),
+        ItemTypeInfo::Weapon(x) => x.base.keys().chain(x.identifications.keys()).collect(),
+        ItemTypeInfo::Ingredient(x) => x.identifications.keys().collect(),
+        ItemTypeInfo::Charm(x) => x.base.keys().chain(x.identifications.keys()).collect(),
+        ItemTypeInfo::Armour(x) => x.base.keys().chain(x.identifications.keys()).collect(),
+        ItemTypeInfo::Tool(_) | ItemTypeInfo::Material(_) => BTreeSet::new(),
+    }
+}
+
+fn major_ids(item: &Item) -> Vec<&MajorId> {
+    match &item.item_type {
+        ItemTypeInfo::Accessory(x) => x.major_ids.keys().collect(),
+        ItemTypeInfo::Weapon(x) => x.major_ids.keys().collect(),
+        ItemTypeInfo::Armour(x) => x.major_ids.keys().collect(),
+        _ => Vec::new(),
+    }
+}
+
+impl WynnClient {
+    /// Downloads the whole item database and indexes it, see [`ItemIndex`].
+    pub async fn item_index(&self) -> Result<ItemIndex, WynnApiError> {
+        Ok(ItemIndex::new(self.item_database_full().await?))
+    }
+}
+
+pub async fn item_index() -> Result<ItemIndex, WynnApiError> {
+    WynnClient::global().item_index().await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        item::{search_item_full, ArmourType, IngredientTier, ItemRarity, WeaponType},
+        mock::{MockResponse, MockServer},
+    };
+
+    /// items in the format of the item database, one or two of every type
+    const ITEMS: &str = r#"{
+        "Photon": {
+            "internalName": "Photon", "type": "weapon", "weaponType": "wand", "attackSpeed": "very_fast",
+            "icon": null, "rarity": "unique", "requirements": {"level": 49, "classRequirement": "mage"},
+            "base": {"baseDamage": {"min": 20, "raw": 25, "max": 30}},
+            "identifications": {"rawIntelligence": 7, "walkSpeed": {"min": 4, "raw": 12, "max": 16}}
+        },
+        "Warp": {
+            "internalName": "Warp", "type": "weapon", "weaponType": "wand", "attackSpeed": "super_fast",
+            "icon": null, "rarity": "legendary", "requirements": {"level": 98, "agility": 110},
+            "identifications": {"rawAgility": 15, "walkSpeed": {"min": 63, "raw": 180, "max": 234}},
+            "majorIds": {"Freerunner": "+Freerunner: ..."}
+        },
+        "Boreal-Patterned Crown": {
+            "internalName": "Boreal-Patterned Crown", "type": "armour", "armourType": "helmet",
+            "rarity": "rare", "requirements": {"level": 80},
+            "base": {"baseHealth": 2600}, "identifications": {"healthRegen": {"min": 5, "raw": 15, "max": 20}}
+        },
+        "Diamond Static Necklace": {
+            "internalName": "Diamond Static Necklace", "type": "accessory", "accessoryType": "necklace",
+            "icon": null, "rarity": "rare", "requirements": {"level": 95},
+            "identifications": {"rawDexterity": 5}
+        },
+        "Refined Pickaxe": {
+            "internalName": "Refined Pickaxe", "type": "tool", "toolType": "pickaxe", "icon": null,
+            "rarity": "common", "requirements": {"level": 70}, "gatheringSpeed": 90
+        },
+        "Glow Bulb Seeds": {
+            "internalName": "Glow Bulb Seeds", "type": "ingredient", "icon": null, "tier": 2,
+            "requirements": {"level": 72, "skills": ["ARMOURING", "TAILORING"]},
+            "consumableOnlyIDs": {}, "itemOnlyIDs": {"durabilityModifier": -30000},
+            "ingredientPositionModifiers": {"above": 20},
+            "identifications": {"walkSpeed": {"min": 6, "raw": 6, "max": 9}}
+        },
+        "Copper Ingot": {
+            "internalName": "Copper Ingot", "type": "material", "icon": null, "identified": true,
+            "tier": 1, "requirements": {"level": 1}, "craftable": ["helmets", "rings"]
+        },
+        "Charm of the Void": {
+            "internalName": "Charm of the Void", "type": "charm", "icon": null, "rarity": "legendary",
+            "requirements": {"level": 100, "levelRange": {"min": 80, "max": 106}}, "raidReward": false,
+            "base": {"rawHealth": 500}
+        },
+        "Mysticism Tome of Photons": {
+            "internalName": "Mysticism Tome of Photons", "type": "tome", "tomeType": "mysticism_tome",
+            "icon": null, "rarity": "mythic", "requirements": {"level": 80}, "raidReward": true,
+            "identifications": {"rawIntelligence": 2}
+        }
+    }"#;
+
+    fn items() -> Map<String, Item> {
+        serde_json::from_str(ITEMS).unwrap()
+    }
+
+    /// queries with the items of [`ITEMS`] that match them
+    fn queries() -> Vec<(ItemQuery, Vec<&'static str>)> {
+        let mut wands = ItemQuery::new();
+        wands.add_item_type(WeaponType::Wand);
+        wands.add_attack_speed(AttackSpeed::SuperFast);
+
+        let mut walk_speed = ItemQuery::new();
+        walk_speed.add_identification(Identification::WalkSpeed);
+
+        let mut health = ItemQuery::new();
+        health.add_identification(Identification::Basehealth);
+        health.add_identification(Identification::HealthRegenPercent);
+
+        let mut high_level = ItemQuery::new();
+        high_level.set_min_level(80);
+        high_level.set_max_level(100);
+
+        let mut tiers = ItemQuery::new();
+        tiers.add_tier(ItemRarity::Legendary);
+        tiers.add_tier(IngredientTier::Ingredient2Star);
+        tiers.set_max_level(98);
+
+        let mut tailoring = ItemQuery::new();
+        tailoring.add_profession(Profession::Tailoring);
+        tailoring.add_profession(Profession::Mining);
+
+        let mut freerunner = ItemQuery::with_query("WARP");
+        freerunner.add_major_id(MajorId::Freerunner);
+
+        let mut helmets = ItemQuery::new();
+        helmets.add_item_type(ArmourType::Helmet);
+        helmets.add_item_type(OtherItemType::Material);
+
+        vec![
+            (
+                ItemQuery::with_query("photon"),
+                vec!["Mysticism Tome of Photons", "Photon"],
+            ),
+            (wands, vec!["Warp"]),
+            (walk_speed, vec!["Glow Bulb Seeds", "Photon", "Warp"]),
+            (health, vec!["Boreal-Patterned Crown"]),
+            (
+                high_level,
+                vec![
+                    "Boreal-Patterned Crown",
+                    "Charm of the Void",
+                    "Diamond Static Necklace",
+                    "Mysticism Tome of Photons",
+                    "Warp",
+                ],
+            ),
+            (tiers, vec!["Glow Bulb Seeds", "Warp"]),
+            (tailoring, vec!["Glow Bulb Seeds", "Refined Pickaxe"]),
+            (freerunner, vec!["Warp"]),
+            (helmets, vec!["Boreal-Patterned Crown", "Copper Ingot"]),
+        ]
+    }
+
+    #[test]
+    fn search() {
+        let index = ItemIndex::new(items());
+        assert_eq!(index.len(), 9);
+        assert_eq!(index.search(&ItemQuery::new()).len(), 9);
+        assert!(index.get("Warp").is_some());
+        assert!(index.get("warp").is_none());
+        for (query, expected) in queries() {
+            let names: Vec<_> = index.search(&query).into_iter().map(|(n, _)| n).collect();
+            assert_eq!(names, expected, "{query:?}");
+        }
+
+        let mut empty = ItemQuery::new();
+        empty.set_min_level(50);
+        empty.set_max_level(10);
+        assert!(index.search(&empty).is_empty());
+    }
+
+    #[tokio::test]
+    async fn index_from_api() {
+        let server = MockServer::start(move |request| {
+            assert_eq!(request.path, "/item/database?fullResult");
+            MockResponse::json(ITEMS)
+        })
+        .await;
+        let client = WynnClient::builder()
+            .base_url(server.url())
+            .build()
+            .unwrap();
+        let index = client.item_index().await.unwrap();
+        assert_eq!(index.len(), 9);
+        assert!(index.get("Photon").is_some());
+    }
+
+    #[tokio::test]
+    #[ignore = "downloads the whole item database"]
+    async fn parity_with_api() {
+        let index = item_index().await.unwrap();
+        for (query, _) in queries() {
+            let remote = search_item_full(&query).await.unwrap();
+            let mut remote: Vec<_> = remote.keys().map(String::as_str).collect();
+            remote.sort();
+            let local: Vec<_> = index.search(&query).into_iter().map(|(n, _)| n).collect();
+            assert_eq!(local, remote, "{query:?}");
+        }
+    }
+}
@@ -1,25 +1,32 @@
+#[cfg(feature = "chrono")]
+use crate::timestamp::{self, InvalidTimestamp};
 use crate::{
-    api_request,
     classes::Class,
     deserialize_with_default,
     guild::Banner,
-    player::{LegacyRankColour, SupportRank},
-    WynnApiError, API_LOCATION,
+    player::{self, LegacyRankColour, SupportRank},
+    retry::RetryPolicy,
+    uuid::{CharacterUuid, GuildUuid, PlayerUuid},
+    WynnApiError, WynnClient,
 };
 use serde::{
     de::{DeserializeOwned, MapAccess, Visitor},
     Deserialize, Deserializer, Serialize,
 };
+use serde_json::Value;
 use std::{
     collections::BTreeMap,
-    fmt::{self, Display},
+    fmt::{self, Debug, Display},
     marker::PhantomData,
+    str::FromStr,
+    time::Duration,
 };
+use thiserror::Error;
 
 #[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct LbGuild {
-    pub uuid: String,
+    pub uuid: GuildUuid,
     pub name: String,
     #[serde(default, deserialize_with = "deserialize_with_default")]
     pub prefix: String,
@@ -33,11 +40,29 @@ pub struct LbGuild {
     pub banner: Option<Banner>,
 }
 
+impl LbGuild {
+    /// The value `leaderboard` is sorted by.
+    pub fn score_on(&self, leaderboard: GuildLbType) -> i64 {
+        match leaderboard {
+            GuildLbType::GuildLevel => self.level as i64,
+            GuildLbType::GuildTerritories => self.territories as i64,
+            GuildLbType::GuildWars => self.wars as i64,
+        }
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl LbGuild {
+    pub fn created_at(&self) -> Result<chrono::DateTime<chrono::Utc>, InvalidTimestamp> {
+        timestamp::parse(&self.created)
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct LbPlayerGlobal {
     pub name: String,
-    pub uuid: String,
+    pub uuid: PlayerUuid,
     pub score: i64,
     pub previous_ranking: i64,
     pub metadata: PlayerMetaData,
@@ -52,9 +77,16 @@ pub struct LbPlayerGlobal {
 pub struct PlayerMetaData {
     /// Some endpoints just don't return this value even when documented that they should
     pub xp: Option<u64>,
+    /// playtime in hours, see [`PlayerMetaData::playtime_duration`]
     pub playtime: f64,
 }
 
+impl PlayerMetaData {
+    pub fn playtime_duration(&self) -> Duration {
+        player::hours(self.playtime)
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct RaidMetaData {
@@ -66,11 +98,11 @@ pub struct RaidMetaData {
 #[serde(rename_all = "camelCase")]
 pub struct LbPlayerProfile {
     pub name: String,
-    pub uuid: String,
+    pub uuid: PlayerUuid,
     pub score: i64,
     pub previous_ranking: i64,
     pub metadata: PlayerMetaData,
-    pub character_uuid: String,
+    pub character_uuid: CharacterUuid,
     pub character_type: Class,
     pub rank: String,
     pub rank_badge: Option<String>,
@@ -82,7 +114,7 @@ pub struct LbPlayerProfile {
 #[serde(rename_all = "camelCase")]
 pub struct LbRaidPlayer {
     pub name: String,
-    pub uuid: String,
+    pub uuid: PlayerUuid,
     pub score: i64,
     pub previous_ranking: i64,
     pub metadata: RaidMetaData,
@@ -96,7 +128,7 @@ pub struct LbRaidPlayer {
 #[serde(rename_all = "camelCase")]
 pub struct LbRaidGuild {
     pub name: String,
-    pub uuid: String,
+    pub uuid: GuildUuid,
     #[serde(default, deserialize_with = "deserialize_with_default")]
     pub score: u64,
     pub previous_ranking: i64,
@@ -104,7 +136,7 @@ pub struct LbRaidGuild {
     pub banner: Option<Banner>,
 }
 
-#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Copy)]
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone, Copy)]
 #[serde(rename_all = "camelCase")]
 pub enum GuildLbType {
     GuildLevel,
@@ -112,7 +144,7 @@ pub enum GuildLbType {
     GuildWars,
 }
 
-#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Copy)]
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone, Copy)]
 #[serde(rename_all = "camelCase")]
 pub enum PlayerProfileLbType {
     WoodcuttingLevel,
@@ -143,7 +175,7 @@ pub enum PlayerProfileLbType {
     HicContent,
 }
 
-#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Copy)]
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone, Copy)]
 #[serde(rename_all = "camelCase")]
 pub enum PlayerGlobalLbType {
     ProfessionsGlobalLevel,
@@ -157,7 +189,7 @@ pub enum PlayerGlobalLbType {
     TnaCompletion,
 }
 
-#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Copy)]
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone, Copy)]
 #[serde(rename_all = "camelCase")]
 pub enum RaidPlayerLbType {
     NogSrPlayers,
@@ -166,7 +198,7 @@ pub enum RaidPlayerLbType {
     TnaSrPlayers,
 }
 
-#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Copy)]
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone, Copy)]
 #[serde(rename_all = "camelCase")]
 pub enum RaidGuildLbType {
     NogSrGuilds,
@@ -175,6 +207,78 @@ pub enum RaidGuildLbType {
     TnaSrGuilds,
 }
 
+impl GuildLbType {
+    pub const ALL: [GuildLbType; 3] = {
+        use GuildLbType::*;
+        [GuildLevel, GuildTerritories, GuildWars]
+    };
+}
+
+impl PlayerProfileLbType {
+    pub const ALL: [PlayerProfileLbType; 26] = {
+        use PlayerProfileLbType::*;
+        [
+            WoodcuttingLevel,
+            MiningLevel,
+            FishingLevel,
+            FarmingLevel,
+            AlchemismLevel,
+            ArmouringLevel,
+            CookingLevel,
+            JewelingLevel,
+            ScribingLevel,
+            TailoringLevel,
+            WeaponsmithingLevel,
+            WoodworkingLevel,
+            PlayerContent,
+            CombatSoloLevel,
+            ProfessionsSoloLevel,
+            TotalSoloLevel,
+            HardcoreLegacyLevel,
+            IronmanContent,
+            UltimateIronmanContent,
+            HardcoreContent,
+            CraftsmanContent,
+            HuntedContent,
+            HuicContent,
+            HuichContent,
+            HichContent,
+            HicContent,
+        ]
+    };
+}
+
+impl PlayerGlobalLbType {
+    pub const ALL: [PlayerGlobalLbType; 9] = {
+        use PlayerGlobalLbType::*;
+        [
+            ProfessionsGlobalLevel,
+            CombatGlobalLevel,
+            TotalGlobalLevel,
+            GlobalPlayerContent,
+            NogCompletion,
+            TccCompletion,
+            NolCompletion,
+            WarsCompletion,
+            TnaCompletion,
+        ]
+    };
+}
+
+impl RaidPlayerLbType {
+    pub const ALL: [RaidPlayerLbType; 4] = {
+        use RaidPlayerLbType::*;
+        [NogSrPlayers, NolSrPlayers, TccSrPlayers, TnaSrPlayers]
+    };
+}
+
+impl RaidGuildLbType {
+    pub const ALL: [RaidGuildLbType; 4] = {
+        use RaidGuildLbType::*;
+        [NogSrGuilds, NolSrGuilds, TccSrGuilds, TnaSrGuilds]
+    };
+}
+
 macro_rules! display {
     ($t:ty) => {
         impl Display for $t {
@@ -195,6 +299,226 @@ display!(PlayerGlobalLbType);
 display!(RaidPlayerLbType);
 display!(RaidGuildLbType);
 
+/// A type of leaderboard, with the type of the entries on that leaderboard.
+pub trait LbType: Display + Copy {
+    type Entry: LeaderboardEntry + DeserializeOwned;
+}
+
+impl LbType for GuildLbType {
+    type Entry = LbGuild;
+}
+
+impl LbType for PlayerProfileLbType {
+    type Entry = LbPlayerProfile;
+}
+
+impl LbType for PlayerGlobalLbType {
+    type Entry = LbPlayerGlobal;
+}
+
+impl LbType for RaidPlayerLbType {
+    type Entry = LbRaidPlayer;
+}
+
+impl LbType for RaidGuildLbType {
+    type Entry = LbRaidGuild;
+}
+
+/// Any leaderboard, see [`WynnClient::fetch_leaderboard`].
+///
+/// Serialized as the name the api uses, like `guildLevel`.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone, Copy)]
+#[serde(untagged)]
+pub enum LeaderboardKind {
+    Guild(GuildLbType),
+    PlayerProfile(PlayerProfileLbType),
+    PlayerGlobal(PlayerGlobalLbType),
+    RaidPlayer(RaidPlayerLbType),
+    RaidGuild(RaidGuildLbType),
+}
+
+impl LeaderboardKind {
+    /// Every leaderboard this crate knows about.
+    pub fn all() -> impl Iterator<Item = LeaderboardKind> {
+        GuildLbType::ALL
+            .into_iter()
+            .map(Self::Guild)
+            .chain(
+                PlayerProfileLbType::ALL
+                    .into_iter()
+                    .map(Self::PlayerProfile),
+            )
+            .chain(PlayerGlobalLbType::ALL.into_iter().map(Self::PlayerGlobal))
+            .chain(RaidPlayerLbType::ALL.into_iter().map(Self::RaidPlayer))
+            .chain(RaidGuildLbType::ALL.into_iter().map(Self::RaidGuild))
+    }
+
+    /// Whether the entries of the leaderboard are guilds instead of players.
+    pub fn is_guild(&self) -> bool {
+        matches!(self, Self::Guild(_) | Self::RaidGuild(_))
+    }
+}
+
+impl Display for LeaderboardKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Guild(t) => Display::fmt(t, f),
+            Self::PlayerProfile(t) => Display::fmt(t, f),
+            Self::PlayerGlobal(t) => Display::fmt(t, f),
+            Self::RaidPlayer(t) => Display::fmt(t, f),
+            Self::RaidGuild(t) => Display::fmt(t, f),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Error)]
+#[error("`{0}` is not a known leaderboard")]
+pub struct ParseLeaderboardKindError(pub String);
+
+impl FromStr for LeaderboardKind {
+    type Err = ParseLeaderboardKindError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        serde_json::from_value(Value::String(s.to_owned()))
+            .map_err(|_| ParseLeaderboardKindError(s.to_owned()))
+    }
+}
+
+macro_rules! kind_from {
+    ($t:ty, $variant:ident) => {
+        impl From<$t> for LeaderboardKind {
+            fn from(value: $t) -> Self {
+                LeaderboardKind::$variant(value)
+            }
+        }
+    };
+}
+
+kind_from!(GuildLbType, Guild);
+kind_from!(PlayerProfileLbType, PlayerProfile);
+kind_from!(PlayerGlobalLbType, PlayerGlobal);
+kind_from!(RaidPlayerLbType, RaidPlayer);
+kind_from!(RaidGuildLbType, RaidGuild);
+
+/// A leaderboard of any [`LeaderboardKind`], with the entries of that kind.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[serde(tag = "kind", content = "leaderboard", rename_all = "camelCase")]
+pub enum AnyLeaderboard {
+    Guild(Leaderboard<LbGuild>),
+    PlayerProfile(Leaderboard<LbPlayerProfile>),
+    PlayerGlobal(Leaderboard<LbPlayerGlobal>),
+    RaidPlayer(Leaderboard<LbRaidPlayer>),
+    RaidGuild(Leaderboard<LbRaidGuild>),
+}
+
+impl AnyLeaderboard {
+    pub fn len(&self) -> usize {
+        match self {
+            Self::Guild(lb) => lb.leaderboard.len(),
+            Self::PlayerProfile(lb) => lb.leaderboard.len(),
+            Self::PlayerGlobal(lb) => lb.leaderboard.len(),
+            Self::RaidPlayer(lb) => lb.leaderboard.len(),
+            Self::RaidGuild(lb) => lb.leaderboard.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The names of the entries from high to low.
+    pub fn names(&self) -> Vec<&str> {
+        fn names<T: DeserializeOwned + LeaderboardEntry>(lb: &Leaderboard<T>) -> Vec<&str> {
+            lb.leaderboard.iter().map(LeaderboardEntry::name).collect()
+        }
+        match self {
+            Self::Guild(lb) => names(lb),
+            Self::PlayerProfile(lb) => names(lb),
+            Self::PlayerGlobal(lb) => names(lb),
+            Self::RaidPlayer(lb) => names(lb),
+            Self::RaidGuild(lb) => names(lb),
+        }
+    }
+}
+
+/// An entry on a leaderboard, a player or a guild.
+///
+/// The current rank of an entry is its position in the [`Leaderboard`].
+pub trait LeaderboardEntry {
+    /// the uuid of the player or guild
+    type Id: Copy + Ord + Debug;
+
+    fn id(&self) -> Self::Id;
+    fn name(&self) -> &str;
+    /// The value the leaderboard is sorted by.
+    ///
+    /// Guilds have no single score, this is their level, use [`LbGuild::score_on`] for the other guild leaderboards.
+    fn score(&self) -> i64;
+    /// The rank the api says the entry had before, starting at 1.
+    fn previous_rank(&self) -> Option<usize>;
+}
+
+/// A rank the api sent, starting at 1, `None` for 0 and below which the api uses for no rank.
+pub(crate) fn api_rank(ranking: i64) -> Option<usize> {
+    usize::try_from(ranking).ok().filter(|&rank| rank > 0)
+}
+
+macro_rules! leaderboard_entry {
+    ($t:ty, $id:ty, $score:expr, $previous:expr) => {
+        impl LeaderboardEntry for $t {
+            type Id = $id;
+
+            fn id(&self) -> Self::Id {
+                self.uuid
+            }
+
+            fn name(&self) -> &str {
+                &self.name
+            }
+
+            fn score(&self) -> i64 {
+                $score(self)
+            }
+
+            fn previous_rank(&self) -> Option<usize> {
+                $previous(self)
+            }
+        }
+    };
+}
+
+leaderboard_entry!(
+    LbGuild,
+    GuildUuid,
+    |g: &LbGuild| g.score_on(GuildLbType::GuildLevel),
+    |_| None
+);
+leaderboard_entry!(
+    LbPlayerGlobal,
+    PlayerUuid,
+    |p: &LbPlayerGlobal| p.score,
+    |p: &LbPlayerGlobal| api_rank(p.previous_ranking)
+);
+// a player can be on a profile leaderboard with multiple characters
+leaderboard_entry!(
+    LbPlayerProfile,
+    PlayerUuid,
+    |p: &LbPlayerProfile| p.score,
+    |p: &LbPlayerProfile| api_rank(p.previous_ranking)
+);
+leaderboard_entry!(
+    LbRaidPlayer,
+    PlayerUuid,
+    |p: &LbRaidPlayer| p.score,
+    |p: &LbRaidPlayer| api_rank(p.previous_ranking)
+);
+leaderboard_entry!(
+    LbRaidGuild,
+    GuildUuid,
+    |g: &LbRaidGuild| g.score as i64,
+    |g: &LbRaidGuild| api_rank(g.previous_ranking)
+);
+
 #[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
 #[serde(transparent)]
 pub struct Leaderboard<T: DeserializeOwned> {
@@ -209,6 +533,264 @@ impl<T: DeserializeOwned> Leaderboard<T> {
     }
 }
 
+impl<T: DeserializeOwned + LeaderboardEntry> Leaderboard<T> {
+    /// The ranking of the player or guild, starting at 1.
+    ///
+    /// Only the best ranking is returned when a player is on the leaderboard multiple times.
+    pub fn position_of(&self, id: T::Id) -> Option<usize> {
+        self.leaderboard
+            .iter()
+            .position(|entry| entry.id() == id)
+            .map(|i| i + 1)
+    }
+
+    /// The ranking and entry of the player or guild with this name, ignoring case.
+    pub fn find_by_name(&self, name: &str) -> Option<(usize, &T)> {
+        self.leaderboard
+            .iter()
+            .enumerate()
+            .find(|(_, entry)| entry.name().eq_ignore_ascii_case(name))
+            .map(|(i, entry)| (i + 1, entry))
+    }
+
+    /// The `n` entries above and below the player or guild.
+    pub fn neighbourhood(&self, id: T::Id, n: usize) -> Option<Neighbourhood<'_, T>> {
+        let rank = self.position_of(id)?;
+        let i = rank - 1;
+        Some(Neighbourhood {
+            rank,
+            above: &self.leaderboard[i.saturating_sub(n)..i],
+            entry: &self.leaderboard[i],
+            below: &self.leaderboard[i + 1..(i + 1).saturating_add(n).min(self.leaderboard.len())],
+        })
+    }
+}
+
+/// A part of a leaderboard around an entry, see [`Leaderboard::neighbourhood`].
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Neighbourhood<'a, T> {
+    /// the ranking of `entry`, starting at 1
+    pub rank: usize,
+    /// the entries ranked higher, the highest first
+    pub above: &'a [T],
+    pub entry: &'a T,
+    /// the entries ranked lower, the highest first
+    pub below: &'a [T],
+}
+
+impl<T> Neighbourhood<'_, T> {
+    /// The ranking of the first entry in [`above`](Self::above).
+    pub fn first_rank(&self) -> usize {
+        self.rank - self.above.len()
+    }
+}
+
+/// A player or guild on a leaderboard at a point in time.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RankedEntry<Id> {
+    pub id: Id,
+    pub name: String,
+    /// starting at 1
+    pub rank: usize,
+    pub score: i64,
+}
+
+/// A player or guild that is on both snapshots with a different rank or score.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RankChange<Id> {
+    pub id: Id,
+    pub name: String,
+    pub from: usize,
+    pub to: usize,
+    pub score_delta: i64,
+}
+
+impl<Id> RankChange<Id> {
+    /// How many places the entry climbed, negative if it fell.
+    pub fn moved(&self) -> i64 {
+        self.from as i64 - self.to as i64
+    }
+}
+
+/// The differences between two snapshots of the same leaderboard.
+///
+/// A player that is on the leaderboard multiple times (like with multiple characters) is compared by their best rank.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct LeaderboardDiff<Id> {
+    /// on the new snapshot but not on the old one, from high to low
+    pub entered: Vec<RankedEntry<Id>>,
+    /// on the old snapshot but not on the new one, from high to low
+    pub left: Vec<RankedEntry<Id>>,
+    /// on both with a different rank or score, sorted by their new rank
+    pub changes: Vec<RankChange<Id>>,
+}
+
+impl<Id: Copy + Ord + Debug> LeaderboardDiff<Id> {
+    /// Compares two snapshots, `score` is the value the leaderboard is sorted by.
+    ///
+    /// For most leaderboards this is [`LeaderboardEntry::score`],
+    /// for guild leaderboards it depends on the leaderboard, see [`LbGuild::score_on`].
+    pub fn between<T>(old: &Leaderboard<T>, new: &Leaderboard<T>, score: impl Fn(&T) -> i64) -> Self
+    where
+        T: DeserializeOwned + LeaderboardEntry<Id = Id>,
+    {
+        let ranked = |board: &[T]| {
+            let mut ranked = BTreeMap::new();
+            for (i, entry) in board.iter().enumerate() {
+                ranked.entry(entry.id()).or_insert_with(|| RankedEntry {
+                    id: entry.id(),
+                    name: entry.name().to_owned(),
+                    rank: i + 1,
+                    score: score(entry),
+                });
+            }
+            ranked
+        };
+
+        let (old, mut new) = (ranked(&old.leaderboard), ranked(&new.leaderboard));
+        let mut diff = LeaderboardDiff {
+            entered: Vec::new(),
+            left: Vec::new(),
+            changes: Vec::new(),
+        };
+        for (id, before) in old {
+            match new.remove(&id) {
+                None => diff.left.push(before),
+                Some(after) if after.rank != before.rank || after.score != before.score => {
+                    diff.changes.push(RankChange {
+                        id,
+                        name: after.name,
+                        from: before.rank,
+                        to: after.rank,
+                        score_delta: after.score - before.score,
+                    })
+                }
+                Some(_) => {}
+            }
+        }
+        diff.entered = new.into_values().collect();
+        diff.entered.sort_by_key(|e| e.rank);
+        diff.left.sort_by_key(|e| e.rank);
+        diff.changes.sort_by_key(|c| c.to);
+        diff
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entered.is_empty() && self.left.is_empty() && self.changes.is_empty()
+    }
+
+    pub fn change_of(&self, id: Id) -> Option<&RankChange<Id>> {
+        self.changes.iter().find(|c| c.id == id)
+    }
+
+    /// The `n` entries that climbed the most places, the biggest climb first.
+    pub fn climbers(&self, n: usize) -> Vec<&RankChange<Id>> {
+        let mut climbers: Vec<_> = self.changes.iter().filter(|c| c.moved() > 0).collect();
+        climbers.sort_by_key(|c| std::cmp::Reverse(c.moved()));
+        climbers.truncate(n);
+        climbers
+    }
+
+    /// The `n` entries that fell the most places, the biggest fall first.
+    pub fn fallers(&self, n: usize) -> Vec<&RankChange<Id>> {
+        let mut fallers: Vec<_> = self.changes.iter().filter(|c| c.moved() < 0).collect();
+        fallers.sort_by_key(|c| c.moved());
+        fallers.truncate(n);
+        fallers
+    }
+
+    /// The `n` entries that moved the most places in either direction.
+    pub fn biggest_movers(&self, n: usize) -> Vec<&RankChange<Id>> {
+        let mut movers: Vec<_> = self.changes.iter().filter(|c| c.moved() != 0).collect();
+        movers.sort_by_key(|c| std::cmp::Reverse(c.moved().abs()));
+        movers.truncate(n);
+        movers
+    }
+}
+
+/// How [`WynnClient::leaderboard_full`] downloads a whole leaderboard.
+///
+/// The api can only return the top of a leaderboard, so the limit is doubled every request
+/// until the leaderboard has less entries than the limit.
+/// Asking for everything in a single request makes the api fail on big leaderboards.
+/// Every request downloads the top again, so all requests together download at most twice `max` entries.
+#[derive(Debug, PartialEq, Clone)]
+pub struct LeaderboardPaging {
+    /// the limit of the first request
+    pub first: u32,
+    /// the highest limit to request, the leaderboard is cut off there
+    pub max: u32,
+    /// the most entries the api returns for one request, if known
+    ///
+    /// No request asks for more, and a response of exactly this many entries counts as cut off.
+    pub cap: Option<u32>,
+    /// the time to wait between requests
+    pub delay: Duration,
+    /// the policy every request is retried with
+    pub retry: RetryPolicy,
+}
+
+impl Default for LeaderboardPaging {
+    /// Starts at 1000 entries, stops at 10000, and waits a second between requests.
+    fn default() -> Self {
+        Self {
+            first: 1000,
+            max: 10_000,
+            cap: None,
+            delay: Duration::from_secs(1),
+            retry: RetryPolicy::default(),
+        }
+    }
+}
+
+impl LeaderboardPaging {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn first(mut self, first: u32) -> Self {
+        self.first = first.max(1);
+        self
+    }
+
+    pub fn max(mut self, max: u32) -> Self {
+        self.max = max.max(1);
+        self
+    }
+
+    pub fn cap(mut self, cap: u32) -> Self {
+        self.cap = Some(cap.max(1));
+        self
+    }
+
+    pub fn delay(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+
+    pub fn retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+}
+
+/// A leaderboard downloaded by [`WynnClient::leaderboard_full`].
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[serde(rename_all = "camelCase", bound(deserialize = "T: DeserializeOwned"))]
+pub struct FullLeaderboard<T: DeserializeOwned> {
+    pub board: Leaderboard<T>,
+    /// whether the leaderboard may have more entries than were downloaded
+    ///
+    /// This is the case when it was cut off at [`LeaderboardPaging::max`] or [`LeaderboardPaging::cap`],
+    /// or when the api returned the same amount of entries for a bigger limit, which means it caps the limit itself.
+    /// A leaderboard with exactly that many entries can't be told apart from a cut off one.
+    pub truncated: bool,
+}
+
 pub(crate) fn deserialize_to_vec<'de, D, T>(deserializer: D) -> Result<Vec<T>, D::Error>
 where
     D: Deserializer<'de>,
@@ -263,59 +845,195 @@ where
     deserializer.deserialize_map(MapToVecVisitor { _ph: PhantomData })
 }
 
+impl WynnClient {
+    /// The top `limit` entries of a leaderboard.
+    pub async fn leaderboard<L: LbType>(
+        &self,
+        leaderboard: L,
+        limit: u16,
+    ) -> Result<Leaderboard<L::Entry>, WynnApiError> {
+        self.api_request(&format!("/leaderboards/{leaderboard}?resultLimit={limit}"))
+            .await
+    }
+
+    pub async fn leaderboard_guild(
+        &self,
+        player_profile: GuildLbType,
+        limit: u16,
+    ) -> Result<Leaderboard<LbGuild>, WynnApiError> {
+        self.leaderboard(player_profile, limit).await
+    }
+
+    pub async fn leaderboard_player_profile(
+        &self,
+        player_profile: PlayerProfileLbType,
+        limit: u16,
+    ) -> Result<Leaderboard<LbPlayerProfile>, WynnApiError> {
+        self.leaderboard(player_profile, limit).await
+    }
+
+    pub async fn leaderboard_player_global(
+        &self,
+        player_global: PlayerGlobalLbType,
+        limit: u16,
+    ) -> Result<Leaderboard<LbPlayerGlobal>, WynnApiError> {
+        self.leaderboard(player_global, limit).await
+    }
+
+    pub async fn leaderboard_raid_player(
+        &self,
+        player_raid: RaidPlayerLbType,
+        limit: u16,
+    ) -> Result<Leaderboard<LbRaidPlayer>, WynnApiError> {
+        self.leaderboard(player_raid, limit).await
+    }
+
+    pub async fn leaderboard_raid_guild(
+        &self,
+        guild_raid: RaidGuildLbType,
+        limit: u16,
+    ) -> Result<Leaderboard<LbRaidGuild>, WynnApiError> {
+        self.leaderboard(guild_raid, limit).await
+    }
+
+    /// The top `limit` entries of any leaderboard.
+    pub async fn fetch_leaderboard(
+        &self,
+        kind: LeaderboardKind,
+        limit: u16,
+    ) -> Result<AnyLeaderboard, WynnApiError> {
+        Ok(match kind {
+            LeaderboardKind::Guild(t) => AnyLeaderboard::Guild(self.leaderboard(t, limit).await?),
+            LeaderboardKind::PlayerProfile(t) => {
+                AnyLeaderboard::PlayerProfile(self.leaderboard(t, limit).await?)
+            }
+            LeaderboardKind::PlayerGlobal(t) => {
+                AnyLeaderboard::PlayerGlobal(self.leaderboard(t, limit).await?)
+            }
+            LeaderboardKind::RaidPlayer(t) => {
+                AnyLeaderboard::RaidPlayer(self.leaderboard(t, limit).await?)
+            }
+            LeaderboardKind::RaidGuild(t) => {
+                AnyLeaderboard::RaidGuild(self.leaderboard(t, limit).await?)
+            }
+        })
+    }
+
+    /// The names of every leaderboard the api has right now, including ones this crate doesn't know yet.
+    ///
+    /// Parse a name into a [`LeaderboardKind`] to find out if it is known.
+    pub async fn leaderboard_types(&self) -> Result<Vec<String>, WynnApiError> {
+        self.api_request("/leaderboards/types").await
+    }
+
+    /// Downloads the whole leaderboard, or the top [`LeaderboardPaging::max`] entries, see [`LeaderboardPaging`].
+    ///
+    /// Check [`FullLeaderboard::truncated`] to find out if the leaderboard was cut off.
+    pub async fn leaderboard_full<L: LbType>(
+        &self,
+        leaderboard: L,
+        paging: &LeaderboardPaging,
+    ) -> Result<FullLeaderboard<L::Entry>, WynnApiError> {
+        let client = self.with_retry(paging.retry.clone());
+        let max = paging.cap.map_or(paging.max, |cap| cap.min(paging.max));
+        let mut limit = paging.first.min(max);
+        let mut previous = None;
+        loop {
+            let board: Leaderboard<L::Entry> = client
+                .api_request(&format!("/leaderboards/{leaderboard}?resultLimit={limit}"))
+                .await?;
+            let len = board.leaderboard.len();
+            if len >= limit as usize && limit < max {
+                previous = Some(len);
+                tokio::time::sleep(paging.delay).await;
+                limit = limit.saturating_mul(2).min(max);
+                continue;
+            }
+            let truncated = len >= limit as usize
+                || paging.cap.is_some_and(|cap| len >= cap as usize)
+                || previous == Some(len);
+            return Ok(FullLeaderboard { board, truncated });
+        }
+    }
+}
+
+pub async fn leaderboard<L: LbType>(
+    leaderboard: L,
+    limit: u16,
+) -> Result<Leaderboard<L::Entry>, WynnApiError> {
+    WynnClient::global().leaderboard(leaderboard, limit).await
+}
+
 pub async fn leaderboard_guild(
     player_profile: GuildLbType,
     limit: u16,
 ) -> Result<Leaderboard<LbGuild>, WynnApiError> {
-    api_request(&format!(
-        "{API_LOCATION}/leaderboards/{player_profile}?resultLimit={limit}"
-    ))
-    .await
+    WynnClient::global()
+        .leaderboard_guild(player_profile, limit)
+        .await
 }
 
 pub async fn leaderboard_player_profile(
     player_profile: PlayerProfileLbType,
     limit: u16,
 ) -> Result<Leaderboard<LbPlayerProfile>, WynnApiError> {
-    api_request(&format!(
-        "{API_LOCATION}/leaderboards/{player_profile}?resultLimit={limit}"
-    ))
-    .await
+    WynnClient::global()
+        .leaderboard_player_profile(player_profile, limit)
+        .await
 }
 
 pub async fn leaderboard_player_global(
     player_global: PlayerGlobalLbType,
     limit: u16,
 ) -> Result<Leaderboard<LbPlayerGlobal>, WynnApiError> {
-    api_request(&format!(
-        "{API_LOCATION}/leaderboards/{player_global}?resultLimit={limit}"
-    ))
-    .await
+    WynnClient::global()
+        .leaderboard_player_global(player_global, limit)
+        .await
 }
 
 pub async fn leaderboard_raid_player(
     player_raid: RaidPlayerLbType,
     limit: u16,
 ) -> Result<Leaderboard<LbRaidPlayer>, WynnApiError> {
-    api_request(&format!(
-        "{API_LOCATION}/leaderboards/{player_raid}?resultLimit={limit}"
-    ))
-    .await
+    WynnClient::global()
+        .leaderboard_raid_player(player_raid, limit)
+        .await
 }
 
 pub async fn leaderboard_raid_guild(
     guild_raid: RaidGuildLbType,
     limit: u16,
 ) -> Result<Leaderboard<LbRaidGuild>, WynnApiError> {
-    api_request(&format!(
-        "{API_LOCATION}/leaderboards/{guild_raid}?resultLimit={limit}"
-    ))
-    .await
+    WynnClient::global()
+        .leaderboard_raid_guild(guild_raid, limit)
+        .await
+}
+
+pub async fn fetch_leaderboard(
+    kind: LeaderboardKind,
+    limit: u16,
+) -> Result<AnyLeaderboard, WynnApiError> {
+    WynnClient::global().fetch_leaderboard(kind, limit).await
+}
+
+pub async fn leaderboard_types() -> Result<Vec<String>, WynnApiError> {
+    WynnClient::global().leaderboard_types().await
+}
+
+pub async fn leaderboard_full<L: LbType>(
+    leaderboard: L,
+    paging: &LeaderboardPaging,
+) -> Result<FullLeaderboard<L::Entry>, WynnApiError> {
+    WynnClient::global()
+        .leaderboard_full(leaderboard, paging)
+        .await
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::mock::{MockResponse, MockServer};
+    use std::sync::atomic::{AtomicBool, Ordering};
 
     #[tokio::test]
     async fn guild_leaderboards() {
@@ -419,4 +1137,210 @@ mod tests {
         dbg!(&t);
         assert!(t.is_ok());
     }
+
+    /// a guild level leaderboard with `size` guilds, of which the first `limit` are returned
+    fn guild_board(size: usize, limit: usize) -> String {
+        let guilds: Vec<String> = (1..=size.min(limit))
+            .map(|i| {
+                format!(
+                    r#""{i}": {{"uuid": "{}", "name": "Guild {i}", "prefix": "G{i}", "level": {}, "xp": 0, "members": 1, "territories": 0, "wars": 0, "created": "2024-09-01T00:00:00.000Z", "banner": null}}"#,
+                    GuildUuid::from_u128(i as u128),
+                    1000 - i
+                )
+            })
+            .collect();
+        format!("{{{}}}", guilds.join(", "))
+    }
+
+    #[tokio::test]
+    async fn full_leaderboard() {
+        let failed = AtomicBool::new(false);
+        let server = MockServer::start(move |request| {
+            let (path, limit) = request.path.split_once("?resultLimit=").unwrap();
+            assert_eq!(path, "/leaderboards/guildLevel");
+            let limit: usize = limit.parse().unwrap();
+            // the first request of the limit 200 fails
+            if limit == 200 && !failed.swap(true, Ordering::SeqCst) {
+                return MockResponse::status(503, "");
+            }
+            MockResponse::json(guild_board(250, limit))
+        })
+        .await;
+        let client = WynnClient::builder()
+            .base_url(server.url())
+            .build()
+            .unwrap();
+        let paging = LeaderboardPaging::new()
+            .first(100)
+            .delay(Duration::from_millis(1))
+            .retry(RetryPolicy::new().base_delay(Duration::from_millis(1)));
+        let board = client
+            .leaderboard_full(GuildLbType::GuildLevel, &paging)
+            .await
+            .unwrap();
+        assert_eq!(board.board.leaderboard.len(), 250);
+        assert!(!board.truncated);
+        // 100, 200 twice and 400
+        assert_eq!(server.request_count(), 4);
+
+        let board = client
+            .leaderboard_full(GuildLbType::GuildLevel, &paging.clone().max(150))
+            .await
+            .unwrap();
+        assert_eq!(board.board.leaderboard.len(), 150);
+        assert!(board.truncated);
+
+        // a known cap is never exceeded
+        let board = client
+            .leaderboard_full(GuildLbType::GuildLevel, &paging.cap(200))
+            .await
+            .unwrap();
+        assert_eq!(board.board.leaderboard.len(), 200);
+        assert!(board.truncated);
+    }
+
+    #[tokio::test]
+    async fn capped_leaderboard() {
+        // the api returns at most 200 entries, no matter the limit
+        let server = MockServer::start(move |request| {
+            let (_, limit) = request.path.split_once("?resultLimit=").unwrap();
+            let limit: usize = limit.parse().unwrap();
+            MockResponse::json(guild_board(1000, limit.min(200)))
+        })
+        .await;
+        let client = WynnClient::builder()
+            .base_url(server.url())
+            .build()
+            .unwrap();
+        assert_eq!(LeaderboardPaging::default().max, 10_000);
+        let paging = LeaderboardPaging::new()
+            .first(100)
+            .delay(Duration::from_millis(1));
+        let board = client
+            .leaderboard_full(GuildLbType::GuildLevel, &paging)
+            .await
+            .unwrap();
+        assert_eq!(board.board.leaderboard.len(), 200);
+        assert!(board.truncated);
+        // 100, 200 and 400
+        assert_eq!(server.request_count(), 3);
+    }
+
+    #[test]
+    fn rank_lookup() {
+        let board: Leaderboard<LbGuild> = serde_json::from_str(&guild_board(10, 10)).unwrap();
+        let uuid = |i| GuildUuid::from_u128(i);
+        assert_eq!(board.position_of(uuid(4)), Some(4));
+        assert_eq!(board.position_of(uuid(11)), None);
+        let (rank, guild) = board.find_by_name("guild 7").unwrap();
+        assert_eq!((rank, guild.uuid), (7, uuid(7)));
+
+        let around = board.neighbourhood(uuid(2), 3).unwrap();
+        assert_eq!(around.rank, 2);
+        assert_eq!(around.first_rank(), 1);
+        assert_eq!(around.above.len(), 1);
+        assert_eq!(around.entry.name, "Guild 2");
+        let below: Vec<_> = around.below.iter().map(|g| g.uuid).collect();
+        assert_eq!(below, [uuid(3), uuid(4), uuid(5)]);
+        let around = board.neighbourhood(uuid(10), 3).unwrap();
+        assert_eq!((around.above.len(), around.below.len()), (3, 0));
+        let around = board.neighbourhood(uuid(5), usize::MAX).unwrap();
+        assert_eq!((around.above.len(), around.below.len()), (4, 5));
+    }
+
+    #[test]
+    fn kinds() {
+        assert_eq!(LeaderboardKind::all().count(), 46);
+        for kind in LeaderboardKind::all() {
+            assert_eq!(kind.to_string().parse::<LeaderboardKind>(), Ok(kind));
+        }
+        let kind: LeaderboardKind = "tnaSrGuilds".parse().unwrap();
+        assert_eq!(kind, RaidGuildLbType::TnaSrGuilds.into());
+        assert!(kind.is_guild());
+        assert_eq!(serde_json::to_string(&kind).unwrap(), r#""tnaSrGuilds""#);
+        assert!("someNewBoard".parse::<LeaderboardKind>().is_err());
+    }
+
+    #[tokio::test]
+    async fn fetch_any_leaderboard() {
+        let server = MockServer::start(|request| match request.path.as_str() {
+            "/leaderboards/types" => MockResponse::json(r#"["guildLevel", "someNewBoard"]"#),
+            "/leaderboards/guildLevel?resultLimit=3" => MockResponse::json(guild_board(10, 3)),
+            path => panic!("unexpected request {path}"),
+        })
+        .await;
+        let client = WynnClient::builder()
+            .base_url(server.url())
+            .build()
+            .unwrap();
+        let types = client.leaderboard_types().await.unwrap();
+        let known: Vec<_> = types
+            .iter()
+            .filter_map(|t| t.parse::<LeaderboardKind>().ok())
+            .collect();
+        assert_eq!(known, [LeaderboardKind::Guild(GuildLbType::GuildLevel)]);
+
+        let board = client.fetch_leaderboard(known[0], 3).await.unwrap();
+        assert!(matches!(board, AnyLeaderboard::Guild(_)));
+        assert_eq!(board.names(), ["Guild 1", "Guild 2", "Guild 3"]);
+    }
+
+    #[test]
+    fn diff() {
+        // (guild, score) from high to low, the score is stored in `field`
+        let board_of = |field: &str, guilds: &[(u128, u64)]| -> Leaderboard<LbGuild> {
+            let guilds: Vec<_> = guilds
+                .iter()
+                .enumerate()
+                .map(|(i, &(guild, score))| {
+                    let mut json = serde_json::json!({
+                        "uuid": GuildUuid::from_u128(guild), "name": format!("Guild {guild}"), "level": 50,
+                        "xp": 0, "members": 1, "territories": 0, "wars": 0,
+                        "created": "2024-09-01T00:00:00.000Z", "banner": null
+                    });
+                    json[field] = score.into();
+                    ((i + 1).to_string(), json)
+                })
+                .collect();
+            serde_json::from_value(serde_json::Value::Object(guilds.into_iter().collect())).unwrap()
+        };
+        let board = |guilds: &[(u128, u64)]| board_of("level", guilds);
+        let old = board(&[(1, 100), (2, 90), (3, 80), (4, 70), (5, 60)]);
+        let new = board(&[(4, 101), (1, 100), (2, 95), (6, 75), (3, 80)]);
+        let diff = LeaderboardDiff::between(&old, &new, LbGuild::score);
+        let uuid = GuildUuid::from_u128;
+
+        let entered: Vec<_> = diff.entered.iter().map(|e| (e.id, e.rank)).collect();
+        assert_eq!(entered, [(uuid(6), 4)]);
+        let left: Vec<_> = diff.left.iter().map(|e| (e.id, e.rank)).collect();
+        assert_eq!(left, [(uuid(5), 5)]);
+        let changes: Vec<_> = diff
+            .changes
+            .iter()
+            .map(|c| (c.id, c.from, c.to, c.score_delta))
+            .collect();
+        assert_eq!(
+            changes,
+            [
+                (uuid(4), 4, 1, 31),
+                (uuid(1), 1, 2, 0),
+                (uuid(2), 2, 3, 5),
+                (uuid(3), 3, 5, 0),
+            ]
+        );
+        assert_eq!(diff.climbers(5)[0].id, uuid(4));
+        let fallers: Vec<_> = diff.fallers(5).iter().map(|c| c.id).collect();
+        assert_eq!(fallers, [uuid(3), uuid(1), uuid(2)]);
+        let movers: Vec<_> = diff.biggest_movers(2).iter().map(|c| c.moved()).collect();
+        assert_eq!(movers, [3, -2]);
+        assert_eq!(diff.change_of(uuid(2)).unwrap().score_delta, 5);
+        assert!(LeaderboardDiff::between(&new, &new, LbGuild::score).is_empty());
+
+        // on the wars leaderboard the score is the amount of wars, not the level
+        let old = board_of("wars", &[(1, 500), (2, 300)]);
+        let new = board_of("wars", &[(2, 600), (1, 510)]);
+        let diff = LeaderboardDiff::between(&old, &new, |g| g.score_on(GuildLbType::GuildWars));
+        assert_eq!(diff.change_of(uuid(2)).unwrap().score_delta, 300);
+        assert_eq!(diff.change_of(uuid(1)).unwrap().score_delta, 10);
+    }
 }
@@ -1,19 +1,38 @@
-use reqwest::Client;
 use serde::{de::DeserializeOwned, Deserialize, Deserializer, Serialize};
+use serde_json::Value;
 #[allow(unused_imports)]
 use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
-use std::{fmt::Display, str::FromStr, sync::OnceLock};
+use std::{cell::Cell, fmt::Display, str::FromStr, time::Duration};
 use thiserror::Error;
 
 pub mod ability;
+pub mod banner;
+pub mod cache;
 pub mod classes;
+pub mod client;
+pub mod geometry;
 pub mod guild;
+pub mod history;
 pub mod item;
+pub mod item_index;
 pub mod leaderboard;
 pub mod map;
 pub mod news;
 pub mod player;
+pub mod ratelimit;
+pub mod retry;
 pub mod search;
+pub mod store;
+#[cfg(feature = "svg")]
+pub mod svg;
+pub mod timestamp;
+pub mod uuid;
+pub mod watch;
+
+#[cfg(test)]
+mod mock;
+
+pub use client::{WynnClient, WynnClientBuilder};
 
 #[cfg(not(feature = "BTree"))]
 type Map<K, V> = HashMap<K, V>;
@@ -26,8 +45,10 @@ type Map<K, V> = BTreeMap<K, V>;
 type Set<K> = BTreeSet<K>;
 
 pub const API_LOCATION: &str = "https://api.wynncraft.com/v3";
+/// The version of the api this crate is made for.
+pub const API_VERSION: &str = "3.3";
 
-#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy)]
 #[serde(rename_all = "lowercase")]
 pub enum Identifier {
     Username,
@@ -45,46 +66,201 @@ impl Display for Identifier {
 
 #[derive(Debug, Error)]
 pub enum WynnApiError {
+    #[error("`{url}` was not found")]
+    NotFound { url: String, body: Option<Value> },
+    #[error("the api ratelimit was exceeded")]
+    RateLimited {
+        retry_after: Option<Duration>,
+        body: Option<Value>,
+    },
+    #[error("the api had an internal error ({status})")]
+    ServerError {
+        status: u16,
+        retry_after: Option<Duration>,
+        body: Option<Value>,
+    },
+    #[error("the api refused the request ({status})")]
+    UnexpectedStatus { status: u16, body: Option<Value> },
+    #[error("the request timed out")]
+    Timeout(#[source] reqwest::Error),
     #[error("couldn't connect to the api")]
-    ConnectionError(#[from] reqwest::Error),
+    Transport(#[source] reqwest::Error),
+    #[error("couldn't decode the response of `{url}`")]
+    Decode {
+        url: String,
+        /// the part of the response around the error
+        snippet: String,
+        #[source]
+        source: serde_json::Error,
+    },
     #[error("there are multiple choices of what the api can return")]
-    MultipleChoices(Map<String, Map<String, serde_json::Value>>),
-    #[cfg(feature = "no_panic")]
-    #[error("this error should never occur")]
-    DeserializeError(#[from] serde_json::Error),
+    MultipleChoices(Map<String, Map<String, Value>>),
+    #[error("couldn't use the cache store")]
+    StoreError(#[from] store::StoreError),
+    #[error("`{0}` is not cached while the client is offline")]
+    OfflineMiss(String),
 }
 
-static CONNECTION_CLIENT: OnceLock<Client> = OnceLock::new();
+impl From<reqwest::Error> for WynnApiError {
+    fn from(error: reqwest::Error) -> Self {
+        if error.is_timeout() {
+            WynnApiError::Timeout(error)
+        } else {
+            WynnApiError::Transport(error)
+        }
+    }
+}
 
-async fn api_request<T>(link: &str) -> Result<T, WynnApiError>
-where
-    T: DeserializeOwned,
-{
-    let client = CONNECTION_CLIENT.get_or_init(Client::new);
-    let response = client.get(link).send().await?;
-    let response = response.error_for_status()?;
-    let multi_selector = response.status().is_redirection();
-    let text = response.text().await?;
-    deserialize_data(link, &text, multi_selector)
+impl WynnApiError {
+    /// Creates the error for a response with a 4xx or 5xx status code.
+    pub fn from_status(
+        status: u16,
+        url: &str,
+        retry_after: Option<Duration>,
+        body: Option<Value>,
+    ) -> Self {
+        match status {
+            404 => WynnApiError::NotFound {
+                url: url.to_owned(),
+                body,
+            },
+            429 => WynnApiError::RateLimited { retry_after, body },
+            500..=599 => WynnApiError::ServerError {
+                status,
+                retry_after,
+                body,
+            },
+            _ => WynnApiError::UnexpectedStatus { status, body },
+        }
+    }
+
+    /// The http status code of the response that caused this error.
+    pub fn status(&self) -> Option<u16> {
+        match self {
+            WynnApiError::NotFound { .. } => Some(404),
+            WynnApiError::RateLimited { .. } => Some(429),
+            WynnApiError::ServerError { status, .. }
+            | WynnApiError::UnexpectedStatus { status, .. } => Some(*status),
+            WynnApiError::MultipleChoices(_) => Some(300),
+            _ => None,
+        }
+    }
+
+    /// The json error the api sent with the response, if there was one.
+    pub fn body(&self) -> Option<&Value> {
+        match self {
+            WynnApiError::NotFound { body, .. }
+            | WynnApiError::RateLimited { body, .. }
+            | WynnApiError::ServerError { body, .. }
+            | WynnApiError::UnexpectedStatus { body, .. } => body.as_ref(),
+            _ => None,
+        }
+    }
+
+    /// How long the api asked to wait before trying again.
+    pub fn retry_after(&self) -> Option<Duration> {
+        match self {
+            WynnApiError::RateLimited { retry_after, .. }
+            | WynnApiError::ServerError { retry_after, .. } => *retry_after,
+            _ => None,
+        }
+    }
+
+    /// Whether sending the same request again could succeed.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            WynnApiError::RateLimited { .. }
+            | WynnApiError::ServerError { .. }
+            | WynnApiError::Timeout(_) => true,
+            WynnApiError::Transport(e) => e.is_connect() || e.is_request() || e.is_body(),
+            _ => false,
+        }
+    }
+
+    /// The candidates of [`WynnApiError::MultipleChoices`] with their key as `uuid`,
+    /// candidates that don't decode as `T` are left out.
+    pub(crate) fn candidates<T: DeserializeOwned>(&self) -> Option<Vec<T>> {
+        let WynnApiError::MultipleChoices(choices) = self else {
+            return None;
+        };
+        let candidates = choices
+            .iter()
+            .filter_map(|(uuid, info)| {
+                let mut info: serde_json::Map<_, _> = info.clone().into_iter().collect();
+                info.insert("uuid".to_owned(), uuid.clone().into());
+                serde_json::from_value(info.into()).ok()
+            })
+            .collect();
+        Some(candidates)
+    }
+}
+
+thread_local! {
+    static LENIENT: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Runs `f` with lenient decoding turned on or off.
+///
+/// While lenient, values of enums that this crate doesn't know about are decoded
+/// into their `Unknown` variant instead of returning an error.
+pub fn with_lenient<R>(lenient: bool, f: impl FnOnce() -> R) -> R {
+    /// restores the previous mode, also when `f` panics
+    struct Restore(bool);
+
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            LENIENT.with(|l| l.set(self.0));
+        }
+    }
+
+    let _restore = Restore(LENIENT.with(|l| l.replace(lenient)));
+    f()
 }
 
-async fn post_api_request<T, Q>(link: &str, content: Q) -> Result<T, WynnApiError>
+/// Whether decoding currently happens in lenient mode, see [`with_lenient`].
+pub fn is_lenient() -> bool {
+    LENIENT.with(|l| l.get())
+}
+
+/// Used for the `Unknown` variant of enums, which is only allowed in lenient mode.
+fn deserialize_unknown<'de, D>(deserializer: D) -> Result<String, D::Error>
 where
-    T: DeserializeOwned,
-    Q: Serialize,
+    D: Deserializer<'de>,
 {
-    let client = CONNECTION_CLIENT.get_or_init(Client::new);
-    let body = serde_json::to_string(&content).expect("valid serializable data");
-    let response = client
-        .post(link)
-        .body(body)
-        .header("Content-Type", "application/json")
-        .send()
-        .await?;
-    let response = response.error_for_status()?;
-    let multi_selector = response.status().is_redirection();
-    let text = response.text().await?;
-    deserialize_data(link, &text, multi_selector)
+    let value = String::deserialize(deserializer)?;
+    if is_lenient() {
+        Ok(value)
+    } else {
+        Err(serde::de::Error::custom(format!("unknown value `{value}`")))
+    }
+}
+
+/// A decoded value together with the json it was decoded from, so no data is lost
+/// when the api returns fields this crate doesn't know about.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Lossless<T> {
+    pub value: T,
+    pub raw: Value,
+}
+
+impl<'de, T: DeserializeOwned> Deserialize<'de> for Lossless<T> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let raw = Value::deserialize(deserializer)?;
+        let value = T::deserialize(&raw).map_err(serde::de::Error::custom)?;
+        Ok(Self { value, raw })
+    }
+}
+
+impl<T> Serialize for Lossless<T> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        self.raw.serialize(serializer)
+    }
 }
 
 fn deserialize_data<T>(link: &str, text: &str, multi_selector: bool) -> Result<T, WynnApiError>
@@ -93,33 +269,39 @@ where
 {
     if multi_selector {
         return Err(WynnApiError::MultipleChoices(
-            match serde_json::from_str(text) {
-                Ok(x) => x,
-                Err(e) => parsing_error(link, e, text)?,
-            },
+            serde_json::from_str(text).map_err(|e| parsing_error(link, e, text))?,
         ));
     }
-    let stats = match serde_json::from_str::<T>(text) {
-        Ok(x) => x,
-        Err(e) => parsing_error(link, e, text)?,
-    };
-    Ok(stats)
+    serde_json::from_str::<T>(text).map_err(|e| parsing_error(link, e, text))
 }
 
-fn parsing_error<T>(link: &str, error: serde_json::Error, text: &str) -> Result<T, WynnApiError> {
-    #[cfg(not(feature = "no_panic"))]
-    // this is only truly unreachable in my perfect world where everything works
-    unreachable!(
-        "wrapper is not working on request: `{link}`\n{error}\n{}",
-        if text.len() < 2000 {
-            text
-        } else {
-            let place = error.column();
-            &text[(place.saturating_sub(200))..(place + 200).min(text.len())]
-        }
-    );
-    #[cfg(feature = "no_panic")]
-    Err(WynnApiError::DeserializeError(error))
+fn parsing_error(link: &str, error: serde_json::Error, text: &str) -> WynnApiError {
+    WynnApiError::Decode {
+        url: link.to_owned(),
+        snippet: error_snippet(&error, text).to_owned(),
+        source: error,
+    }
+}
+
+/// the part of `text` around where `error` happened
+fn error_snippet<'a>(error: &serde_json::Error, text: &'a str) -> &'a str {
+    if text.len() < 2000 {
+        return text;
+    }
+    let line = text
+        .lines()
+        .nth(error.line().saturating_sub(1))
+        .unwrap_or(text);
+    let place = error.column().min(line.len());
+    let mut start = place.saturating_sub(200);
+    while !line.is_char_boundary(start) {
+        start -= 1;
+    }
+    let mut end = (place + 200).min(line.len());
+    while !line.is_char_boundary(end) {
+        end += 1;
+    }
+    &line[start..end]
 }
 
 fn deserialize_with_default<'de, T, D>(deserializer: D) -> Result<T, D::Error>
@@ -170,9 +352,9 @@ impl<'de> Deserialize<'de> for World {
     }
 }
 
-impl ToString for World {
-    fn to_string(&self) -> String {
-        format!("WC{}", self.0)
+impl Display for World {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "WC{}", self.0)
     }
 }
 
@@ -187,3 +369,80 @@ impl World {
         self.0
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        classes::Class,
+        item::{DropType, MajorId},
+        mock::{MockResponse, MockServer},
+    };
+
+    #[test]
+    fn strict_decoding() {
+        let error =
+            deserialize_data::<Map<MajorId, String>>("test", r#"{"New Major Id": ""}"#, false)
+                .unwrap_err();
+        assert!(matches!(error, WynnApiError::Decode { .. }));
+        assert!(serde_json::from_str::<DropType>(r#""newDropType""#).is_err());
+        assert_eq!(
+            serde_json::from_str::<Class>(r#""ARCHER""#).unwrap(),
+            Class::Archer
+        );
+    }
+
+    #[test]
+    fn lenient_decoding() {
+        let ids: Map<MajorId, String> = with_lenient(true, || {
+            serde_json::from_str(r#"{"New Major Id": "a", "Alter Ego": "b"}"#).unwrap()
+        });
+        assert_eq!(ids[&MajorId::Unknown("New Major Id".to_owned())], "a");
+        assert_eq!(ids[&MajorId::AlterEgo], "b");
+        let drop: DropType =
+            with_lenient(true, || serde_json::from_str(r#""newDropType""#).unwrap());
+        assert_eq!(drop, DropType::Unknown("newDropType".to_owned()));
+        assert_eq!(serde_json::to_string(&drop).unwrap(), r#""newDropType""#);
+        assert!(!is_lenient());
+
+        let panicked =
+            std::panic::catch_unwind(|| with_lenient(true, || panic!("decoding failed")));
+        assert!(panicked.is_err());
+        assert!(!is_lenient());
+    }
+
+    #[test]
+    fn lossless() {
+        let text = r#"{"name": "Warrior", "overallDifficulty": 2, "new": true}"#;
+        let class: Lossless<classes::ClassDifficulty> = serde_json::from_str(text).unwrap();
+        assert_eq!(class.value.overall_difficulty, 2);
+        assert_eq!(class.raw["new"], true);
+        assert_eq!(
+            serde_json::to_value(&class).unwrap(),
+            serde_json::from_str::<Value>(text).unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn lenient_client() {
+        let server = MockServer::start(|_| MockResponse::json(r#"["archer", "newClass"]"#)).await;
+        let strict = WynnClient::builder()
+            .base_url(server.url())
+            .build()
+            .unwrap();
+        let error = strict.request::<Vec<Class>>("/classes").await.unwrap_err();
+        assert!(matches!(error, WynnApiError::Decode { .. }));
+
+        let client = WynnClient::builder()
+            .base_url(server.url())
+            .lenient(true)
+            .build()
+            .unwrap();
+        let classes: Lossless<Vec<Class>> = client.request("/classes").await.unwrap();
+        assert_eq!(
+            classes.value,
+            [Class::Archer, Class::Unknown("newClass".to_owned())]
+        );
+        assert_eq!(classes.raw[1], "newClass");
+    }
+}
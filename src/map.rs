@@ -1,4 +1,7 @@
-use crate::{api_request, deserialize_from_string, World, WynnApiError, API_LOCATION};
+use crate::{
+    deserialize_from_string, geometry::MapPosition, uuid::PlayerUuid, World, WynnApiError,
+    WynnClient,
+};
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Copy)]
@@ -20,10 +23,16 @@ pub struct MarkerLocation {
     pub z: i64,
 }
 
+impl MapPosition for MarkerLocation {
+    fn position(&self) -> (i64, i64) {
+        (self.x, self.z)
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct PlayerLocation {
-    pub uuid: String,
+    pub uuid: PlayerUuid,
     pub name: String,
     pub nickname: Option<String>,
     pub server: Option<World>,
@@ -35,10 +44,16 @@ pub struct PlayerLocation {
     pub guild: Vec<FriendLocation>,
 }
 
+impl MapPosition for PlayerLocation {
+    fn position(&self) -> (i64, i64) {
+        (self.x, self.z)
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct FriendLocation {
-    pub uuid: String,
+    pub uuid: PlayerUuid,
     pub name: String,
     pub nickname: Option<String>,
     pub server: Option<World>,
@@ -47,16 +62,36 @@ pub struct FriendLocation {
     pub z: i64,
 }
 
+impl MapPosition for FriendLocation {
+    fn position(&self) -> (i64, i64) {
+        (self.x, self.z)
+    }
+}
+
+impl WynnClient {
+    pub async fn marker_locations(&self) -> Result<Vec<MarkerLocation>, WynnApiError> {
+        self.api_request("/map/locations/markers").await
+    }
+
+    pub async fn player_location(&self) -> Result<Vec<PlayerLocation>, WynnApiError> {
+        self.api_request("/map/locations/player").await
+    }
+
+    pub async fn quest_count(&self) -> Result<Quests, WynnApiError> {
+        self.api_request("/map/quests").await
+    }
+}
+
 pub async fn marker_locations() -> Result<Vec<MarkerLocation>, WynnApiError> {
-    api_request(&format!("{API_LOCATION}/map/locations/markers")).await
+    WynnClient::global().marker_locations().await
 }
 
 pub async fn player_location() -> Result<Vec<PlayerLocation>, WynnApiError> {
-    api_request(&format!("{API_LOCATION}/map/locations/player")).await
+    WynnClient::global().player_location().await
 }
 
 pub async fn quest_count() -> Result<Quests, WynnApiError> {
-    api_request(&format!("{API_LOCATION}/map/quests")).await
+    WynnClient::global().quest_count().await
 }
 
 #[cfg(test)]
@@ -0,0 +1,222 @@
+//! A tiny http server to test the client against without hitting the real api.
+
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpListener,
+};
+
+#[derive(Debug, Clone)]
+pub(crate) struct MockRequest {
+    pub method: String,
+    pub path: String,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+impl MockRequest {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct MockResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+impl MockResponse {
+    pub fn json(body: impl Into<String>) -> Self {
+        Self::status(200, body)
+    }
+
+    pub fn status(status: u16, body: impl Into<String>) -> Self {
+        Self {
+            status,
+            headers: vec![("Content-Type".to_owned(), "application/json".to_owned())],
+            body: body.into(),
+        }
+    }
+
+    pub fn with_header(mut self, name: &str, value: impl ToString) -> Self {
+        self.headers.push((name.to_owned(), value.to_string()));
+        self
+    }
+}
+
+pub(crate) struct MockServer {
+    url: String,
+    requests: Arc<AtomicUsize>,
+}
+
+impl MockServer {
+    pub async fn start<F>(handler: F) -> Self
+    where
+        F: Fn(MockRequest) -> MockResponse + Send + Sync + 'static,
+    {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(AtomicUsize::new(0));
+        let handler = Arc::new(handler);
+        let counter = requests.clone();
+        tokio::spawn(async move {
+            loop {
+                let Ok((mut stream, _)) = listener.accept().await else {
+                    return;
+                };
+                let handler = handler.clone();
+                let counter = counter.clone();
+                tokio::spawn(async move {
+                    let Some(request) = read_request(&mut stream).await else {
+                        return;
+                    };
+                    counter.fetch_add(1, Ordering::SeqCst);
+                    let response = handler(request);
+                    let mut out = format!(
+                        "HTTP/1.1 {} Mock\r\nContent-Length: {}\r\nConnection: close\r\n",
+                        response.status,
+                        response.body.len()
+                    );
+                    for (name, value) in &response.headers {
+                        out.push_str(&format!("{name}: {value}\r\n"));
+                    }
+                    out.push_str("\r\n");
+                    out.push_str(&response.body);
+                    let _ = stream.write_all(out.as_bytes()).await;
+                    let _ = stream.shutdown().await;
+                });
+            }
+        });
+        Self { url, requests }
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    pub fn request_count(&self) -> usize {
+        self.requests.load(Ordering::SeqCst)
+    }
+}
+
+async fn read_request(stream: &mut tokio::net::TcpStream) -> Option<MockRequest> {
+    let mut data = Vec::new();
+    let mut buf = [0; 4096];
+    let header_end = loop {
+        let n = stream.read(&mut buf).await.ok()?;
+        if n == 0 {
+            return None;
+        }
+        data.extend_from_slice(&buf[..n]);
+        if let Some(pos) = data.windows(4).position(|w| w == b"\r\n\r\n") {
+            break pos + 4;
+        }
+    };
+    let head = String::from_utf8_lossy(&data[..header_end]).into_owned();
+    let mut lines = head.split("\r\n");
+    let mut request_line = lines.next()?.split(' ');
+    let method = request_line.next()?.to_owned();
+    let path = request_line.next()?.to_owned();
+    let headers: Vec<(String, String)> = lines
+        .filter_map(|line| line.split_once(':'))
+        .map(|(k, v)| (k.trim().to_owned(), v.trim().to_owned()))
+        .collect();
+    let length = headers
+        .iter()
+        .find(|(k, _)| k.eq_ignore_ascii_case("content-length"))
+        .and_then(|(_, v)| v.parse::<usize>().ok())
+        .unwrap_or(0);
+    while data.len() < header_end + length {
+        let n = stream.read(&mut buf).await.ok()?;
+        if n == 0 {
+            break;
+        }
+        data.extend_from_slice(&buf[..n]);
+    }
+    let body = String::from_utf8_lossy(&data[header_end..]).into_owned();
+    Some(MockRequest {
+        method,
+        path,
+        headers,
+        body,
+    })
+}
+
+/// A player as returned by `/player/{name}`, with `{uuid}` and `{username}` to fill in.
+pub(crate) const PLAYER_STATS: &str = r##"{
+    "username": "{username}",
+    "online": true,
+    "server": "WC1",
+    "activeCharacter": "55a92635-0482-4e0a-b4ff-8284e7c8a326",
+    "uuid": "{uuid}",
+    "rank": "Player",
+    "rankBadge": "rankBadge.png",
+    "legacyRankColour": {"main": "#55FF55", "sub": "#00AA00"},
+    "shortenedRank": "VIP",
+    "supportRank": "vip",
+    "veteran": false,
+    "firstJoin": "2020-06-01T12:00:00.000Z",
+    "lastJoin": "2024-09-01T18:30:00.000Z",
+    "playtime": 123.5,
+    "guild": {"name": "Pleonexia", "prefix": "Plex", "rank": "CAPTAIN", "rankStars": "**"},
+    "globalData": {
+        "wars": 3,
+        "totalLevel": 500,
+        "killedMobs": 10000,
+        "chestsFound": 200,
+        "dungeons": {"total": 5, "list": {"Decrepit Sewers": 3, "Corrupted Decrepit Sewers": 2}},
+        "raids": {"total": 4, "list": {"Nest of the Grootslangs": 3, "The Canyon Colossus": 1}},
+        "completedQuests": 100,
+        "pvp": {"kills": 1, "deaths": 2}
+    },
+    "forumLink": null,
+    "ranking": {"nogCompletion": 42, "combatGlobalLevel": 1000, "someNewBoard": 7},
+    "previousRanking": {"nogCompletion": 45, "combatGlobalLevel": 990},
+    "publicProfile": true
+}"##;
+
+pub(crate) fn player_stats(uuid: &str, username: &str) -> String {
+    PLAYER_STATS
+        .replace("{uuid}", uuid)
+        .replace("{username}", username)
+}
+
+/// A guild as returned by `/guild/{name}?identifier=username`.
+pub(crate) const GUILD: &str = r#"{
+    "uuid": "a1b2c3d4-0000-4000-8000-000000000001",
+    "name": "Test Guild",
+    "prefix": "TEST",
+    "level": 80,
+    "xpPercent": 50,
+    "territories": 3,
+    "wars": 120,
+    "created": "2020-01-01T00:00:00.000Z",
+    "members": {
+        "total": 4,
+        "owner": {
+            "Alice": {"uuid": "0a000000-0000-4000-8000-00000000000a", "online": true, "server": "WC1", "contributed": 5000, "contributionRank": 1, "joined": "2020-01-01T00:00:00.000Z"}
+        },
+        "chief": {},
+        "strategist": {},
+        "captain": {
+            "Bob": {"uuid": "0b000000-0000-4000-8000-00000000000b", "online": false, "server": null, "contributed": 1200, "contributionRank": 2, "joined": "2021-05-01T00:00:00.000Z"}
+        },
+        "recruiter": {
+            "Carol": {"uuid": "0c000000-0000-4000-8000-00000000000c", "online": true, "server": "WC2", "contributed": 300, "contributionRank": 3, "joined": "2022-03-01T00:00:00.000Z"}
+        },
+        "recruit": {
+            "Dave": {"uuid": "0d000000-0000-4000-8000-00000000000d", "online": false, "server": null, "contributed": 0, "contributionRank": 4, "joined": "2024-08-01T00:00:00.000Z"}
+        }
+    },
+    "online": 2,
+    "banner": null,
+    "seasonRanks": {}
+}"#;
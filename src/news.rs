@@ -1,4 +1,6 @@
-use crate::{api_request, deserialize_from_string, WynnApiError, API_LOCATION};
+#[cfg(feature = "chrono")]
+use crate::timestamp::{self, InvalidTimestamp};
+use crate::{deserialize_from_string, WynnApiError, WynnClient};
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
@@ -13,8 +15,21 @@ pub struct NewsArticle {
     pub comments: u64,
 }
 
+#[cfg(feature = "chrono")]
+impl NewsArticle {
+    pub fn published_at(&self) -> Result<chrono::DateTime<chrono::Utc>, InvalidTimestamp> {
+        timestamp::parse(&self.date)
+    }
+}
+
+impl WynnClient {
+    pub async fn latest_news(&self) -> Result<Vec<NewsArticle>, WynnApiError> {
+        self.api_request("/latest-news").await
+    }
+}
+
 pub async fn latest_news() -> Result<Vec<NewsArticle>, WynnApiError> {
-    api_request(&format!("{API_LOCATION}/latest-news")).await
+    WynnClient::global().latest_news().await
 }
 
 #[cfg(test)]
@@ -1,8 +1,24 @@
+#[cfg(feature = "chrono")]
+use crate::timestamp::InvalidTimestamp;
 use crate::{
-    api_request, classes::Class, deserialize_with_default, item::Profession, Identifier, Map, Set,
-    World, WynnApiError, API_LOCATION,
+    classes::Class,
+    deserialize_unknown, deserialize_with_default,
+    guild::GuildRank,
+    is_lenient,
+    item::Profession,
+    leaderboard::{
+        api_rank, LeaderboardKind, PlayerGlobalLbType, PlayerProfileLbType, RaidGuildLbType,
+        RaidPlayerLbType,
+    },
+    timestamp,
+    uuid::{CharacterUuid, PlayerRef, PlayerUuid},
+    Identifier, Map, Set, World, WynnApiError, WynnClient,
 };
 use serde::{Deserialize, Serialize};
+use std::{
+    fmt::Display,
+    time::{Duration, SystemTime},
+};
 
 #[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
 #[serde(rename_all = "camelCase")]
@@ -10,8 +26,8 @@ pub struct PlayerStats {
     pub username: String,
     pub online: bool,
     pub server: Option<World>,
-    pub active_character: Option<String>,
-    pub uuid: String,
+    pub active_character: Option<CharacterUuid>,
+    pub uuid: PlayerUuid,
     pub rank: String,
     pub rank_badge: Option<String>,
     pub legacy_rank_colour: Option<LegacyRankColour>,
@@ -19,11 +35,9 @@ pub struct PlayerStats {
     pub support_rank: SupportRank,
     #[serde(default, deserialize_with = "deserialize_with_default")]
     pub veteran: bool,
-    // could prob be parsed into a datetime
     pub first_join: String,
     pub last_join: String,
-    // maybe change into a Duration or chrono equivalent
-    /// playtime in hours
+    /// playtime in hours, see [`PlayerStats::playtime_duration`]
     pub playtime: f64,
     pub guild: GuildInfo,
     /// data independent of profile
@@ -34,6 +48,81 @@ pub struct PlayerStats {
     pub public_profile: bool,
 }
 
+impl PlayerStats {
+    pub fn playtime_duration(&self) -> Duration {
+        hours(self.playtime)
+    }
+
+    #[cfg(feature = "chrono")]
+    pub fn first_join_at(&self) -> Result<chrono::DateTime<chrono::Utc>, InvalidTimestamp> {
+        timestamp::parse(&self.first_join)
+    }
+
+    #[cfg(feature = "chrono")]
+    pub fn last_join_at(&self) -> Result<chrono::DateTime<chrono::Utc>, InvalidTimestamp> {
+        timestamp::parse(&self.last_join)
+    }
+
+    /// The rankings of the player on every known leaderboard, sorted by the name of the leaderboard,
+    /// as `(leaderboard, rank, previous rank, delta)`.
+    ///
+    /// The delta is how many places the player climbed since the previous ranking, negative if they fell.
+    /// Ranks start at 1, like [`LeaderboardEntry::previous_rank`](crate::leaderboard::LeaderboardEntry::previous_rank)
+    /// a rank of 0 or below counts as no rank.
+    /// Leaderboards this crate doesn't know are left out, see [`PlayerStats::unknown_rankings`].
+    pub fn rankings(
+        &self,
+    ) -> impl Iterator<Item = (LeaderboardKind, usize, Option<usize>, Option<i64>)> + '_ {
+        let mut rankings: Vec<_> = self.ranking.iter().collect();
+        rankings.sort();
+        rankings.into_iter().filter_map(|(name, &rank)| {
+            let kind = name.parse().ok()?;
+            let rank = api_rank(rank)?;
+            let previous = self
+                .previous_ranking
+                .get(name)
+                .and_then(|&previous| api_rank(previous));
+            Some((
+                kind,
+                rank,
+                previous,
+                previous.map(|previous| previous as i64 - rank as i64),
+            ))
+        })
+    }
+
+    /// The rank of the player on `leaderboard` starting at 1, `None` if they aren't on it.
+    pub fn ranking_of(&self, leaderboard: impl Into<LeaderboardKind>) -> Option<usize> {
+        api_rank(*self.ranking.get(&leaderboard.into().to_string())?)
+    }
+
+    /// The rankings on leaderboards this crate doesn't know about yet.
+    pub fn unknown_rankings(&self) -> impl Iterator<Item = (&str, i64)> {
+        self.ranking
+            .iter()
+            .filter(|(name, _)| name.parse::<LeaderboardKind>().is_err())
+            .map(|(name, &rank)| (name.as_str(), rank))
+    }
+}
+
+/// Converts an amount of hours the api sends into a `Duration`, invalid values become zero.
+pub(crate) fn hours(hours: f64) -> Duration {
+    Duration::try_from_secs_f64(hours * 3600.0).unwrap_or_default()
+}
+
+/// Formats a playtime like `123d 4h`, `4h 30m` or `30m`, leaving out smaller units.
+pub fn format_playtime(playtime: Duration) -> String {
+    let minutes = playtime.as_secs() / 60;
+    let (days, hours, minutes) = (minutes / (24 * 60), minutes / 60 % 24, minutes % 60);
+    if days > 0 {
+        format!("{days}d {hours}h")
+    } else if hours > 0 {
+        format!("{hours}h {minutes}m")
+    } else {
+        format!("{minutes}m")
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct LegacyRankColour {
@@ -47,11 +136,17 @@ pub struct GuildInfo {
     pub name: String,
     #[serde(default, deserialize_with = "deserialize_with_default")]
     pub prefix: String,
-    // into enum
-    pub rank: String,
+    pub rank: GuildRank,
     pub rank_stars: String,
 }
 
+impl GuildInfo {
+    /// The rank according to `rank_stars`, which should be the same as `rank`.
+    pub fn rank_from_stars(&self) -> Option<GuildRank> {
+        GuildRank::from_stars(&self.rank_stars)
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct GlobalData {
@@ -71,16 +166,208 @@ pub struct GlobalData {
 #[serde(rename_all = "camelCase")]
 pub struct DungeonInfo {
     pub total: u64,
-    // maybe into enum
-    pub list: Map<String, u64>,
+    pub list: Map<Dungeon, u64>,
 }
 
 #[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Default)]
 #[serde(rename_all = "camelCase")]
 pub struct RaidInfo {
     pub total: u64,
-    // maybe into enum
-    pub list: Map<String, u64>,
+    pub list: Map<Raid, u64>,
+}
+
+/// Enum of content that the api refers to by its name, with an `Other` variant for names this crate doesn't know.
+///
+/// With a level after every name the enum also gets a `level_requirement`.
+macro_rules! named_content {
+    ($(#[$meta:meta])* $name:ident { $($variant:ident => $display:literal, $level:literal;)* }) => {
+        named_content!($(#[$meta])* $name { $($variant => $display;)* });
+
+        impl $name {
+            /// The minimum combat level needed to enter, `None` for `Other`.
+            pub const fn level_requirement(&self) -> Option<u8> {
+                match self {
+                    $($name::$variant => Some($level),)*
+                    $name::Other(_) => None,
+                }
+            }
+        }
+    };
+    ($(#[$meta:meta])* $name:ident { $($variant:ident => $display:literal;)* }) => {
+        $(#[$meta])*
+        #[derive(Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Clone)]
+        pub enum $name {
+            $($variant,)*
+            /// a name this crate doesn't know about
+            Other(String),
+        }
+
+        impl $name {
+            /// Every variant except `Other`.
+            pub const ALL: &'static [$name] = &[$($name::$variant),*];
+
+            /// The name the api and the game use.
+            pub fn name(&self) -> &str {
+                match self {
+                    $($name::$variant => $display,)*
+                    $name::Other(name) => name,
+                }
+            }
+        }
+
+        impl From<&str> for $name {
+            fn from(value: &str) -> Self {
+                match value {
+                    $($display => $name::$variant,)*
+                    other => $name::Other(other.to_owned()),
+                }
+            }
+        }
+
+        impl Display for $name {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.write_str(self.name())
+            }
+        }
+
+        impl Serialize for $name {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.serialize_str(self.name())
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $name {
+            fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                Ok(String::deserialize(deserializer)?.as_str().into())
+            }
+        }
+    };
+}
+
+named_content!(
+    Dungeon {
+        DecrepitSewers => "Decrepit Sewers", 10;
+        InfestedPit => "Infested Pit", 20;
+        LostSanctuary => "Lost Sanctuary", 30;
+        UnderworldCrypt => "Underworld Crypt", 40;
+        TimelostSanctum => "Timelost Sanctum", 40;
+        SandSweptTomb => "Sand-Swept Tomb", 50;
+        IceBarrows => "Ice Barrows", 45;
+        UndergrowthRuins => "Undergrowth Ruins", 55;
+        GalleonsGraveyard => "Galleon's Graveyard", 65;
+        FallenFactory => "Fallen Factory", 90;
+        EldritchOutlook => "Eldritch Outlook", 100;
+        CorruptedDecrepitSewers => "Corrupted Decrepit Sewers", 70;
+        CorruptedInfestedPit => "Corrupted Infested Pit", 74;
+        CorruptedLostSanctuary => "Corrupted Lost Sanctuary", 78;
+        CorruptedUnderworldCrypt => "Corrupted Underworld Crypt", 82;
+        CorruptedSandSweptTomb => "Corrupted Sand-Swept Tomb", 86;
+        CorruptedIceBarrows => "Corrupted Ice Barrows", 90;
+        CorruptedUndergrowthRuins => "Corrupted Undergrowth Ruins", 94;
+        CorruptedGalleonsGraveyard => "Corrupted Galleon's Graveyard", 98;
+    }
+);
+
+named_content!(
+    Raid {
+        NestOfTheGrootslangs => "Nest of the Grootslangs", 54;
+        OrphionsNexusOfLight => "Orphion's Nexus of Light", 80;
+        TheCanyonColossus => "The Canyon Colossus", 95;
+        TheNamelessAnomaly => "The Nameless Anomaly", 103;
+    }
+);
+
+named_content!(
+    /// The quests this crate knows, mini-quests and quests of newer updates are `Other`.
+    Quest {
+        KingsRecruit => "King's Recruit";
+        EnzansBrother => "Enzan's Brother";
+        CookAssistant => "Cook Assistant";
+        TunnelTrouble => "Tunnel Trouble";
+        MushroomMan => "Mushroom Man";
+        ArachnidsAscent => "Arachnids' Ascent";
+        TakingTheTower => "Taking the Tower";
+        CreeperInfiltration => "Creeper Infiltration";
+        PitOfTheDead => "Pit of the Dead";
+        PoisoningThePest => "Poisoning the Pest";
+        RecoverThePast => "Recover the Past";
+        TowerOfAscension => "Tower of Ascension";
+        GraveDigger => "Grave Digger";
+        Cowfusion => "Cowfusion";
+        ZhightIsland => "Zhight Island";
+        BobsLostSoul => "Bob's Lost Soul";
+        TheBiggerPicture => "The Bigger Picture";
+        TheCorruptedVillage => "The Corrupted Village";
+        LexdaleWitchTrials => "Lexdale Witch Trials";
+        ClearingTheCamps => "Clearing the Camps";
+        GreenGloop => "Green Gloop";
+        RiseOfTheQuartron => "Rise of the Quartron";
+        FrostBite => "Frost Bite";
+        DesperateMetal => "Desperate Metal";
+        WrathOfTheMummy => "Wrath of the Mummy";
+        StarThief => "Star Thief";
+        BeneathTheDepths => "Beneath the Depths";
+        StudyingTheCorrupt => "Studying the Corrupt";
+        DwellingWalls => "Dwelling Walls";
+        Reincarnation => "Reincarnation";
+        JungleFever => "Jungle Fever";
+        MurderMystery => "Murder Mystery";
+        TheMaidenTower => "The Maiden Tower";
+        KingdomOfSand => "Kingdom of Sand";
+        FateOfTheFallen => "Fate of the Fallen";
+        TempleOfTheLegends => "Temple of the Legends";
+        MeaningfulHoliday => "Meaningful Holiday";
+        TheQiraHive => "The Qira Hive";
+        AHuntersCalling => "A Hunter's Calling";
+        FallenDelivery => "Fallen Delivery";
+    }
+);
+
+impl Dungeon {
+    pub fn is_corrupted(&self) -> bool {
+        self.name().starts_with("Corrupted ")
+    }
+
+    /// The normal version of a corrupted dungeon, or itself if it isn't corrupted.
+    pub fn uncorrupted(&self) -> Dungeon {
+        match self.name().strip_prefix("Corrupted ") {
+            Some(name) => name.into(),
+            None => self.clone(),
+        }
+    }
+}
+
+impl Raid {
+    pub fn player_lb_type(&self) -> Option<RaidPlayerLbType> {
+        match self {
+            Raid::NestOfTheGrootslangs => Some(RaidPlayerLbType::NogSrPlayers),
+            Raid::OrphionsNexusOfLight => Some(RaidPlayerLbType::NolSrPlayers),
+            Raid::TheCanyonColossus => Some(RaidPlayerLbType::TccSrPlayers),
+            Raid::TheNamelessAnomaly => Some(RaidPlayerLbType::TnaSrPlayers),
+            Raid::Other(_) => None,
+        }
+    }
+
+    pub fn guild_lb_type(&self) -> Option<RaidGuildLbType> {
+        match self {
+            Raid::NestOfTheGrootslangs => Some(RaidGuildLbType::NogSrGuilds),
+            Raid::OrphionsNexusOfLight => Some(RaidGuildLbType::NolSrGuilds),
+            Raid::TheCanyonColossus => Some(RaidGuildLbType::TccSrGuilds),
+            Raid::TheNamelessAnomaly => Some(RaidGuildLbType::TnaSrGuilds),
+            Raid::Other(_) => None,
+        }
+    }
+
+    /// The leaderboard of the amount of completions of this raid.
+    pub fn completion_lb_type(&self) -> Option<PlayerGlobalLbType> {
+        match self {
+            Raid::NestOfTheGrootslangs => Some(PlayerGlobalLbType::NogCompletion),
+            Raid::OrphionsNexusOfLight => Some(PlayerGlobalLbType::NolCompletion),
+            Raid::TheCanyonColossus => Some(PlayerGlobalLbType::TccCompletion),
+            Raid::TheNamelessAnomaly => Some(PlayerGlobalLbType::TnaCompletion),
+            Raid::Other(_) => None,
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
@@ -102,6 +389,7 @@ pub struct Character {
     pub xp_percent: u8,
     pub total_level: u64,
     pub wars: u64,
+    /// playtime in hours, see [`Character::playtime_duration`]
     pub playtime: f64,
     pub mobs_killed: u64,
     pub chests_found: u64,
@@ -113,13 +401,125 @@ pub struct Character {
     /// this field isnt documented for some reason
     pub pre_economy: bool,
     pub pvp: Pvp,
-    // maybe into bitfield?
-    pub gamemode: Set<String>,
+    pub gamemode: Gamemodes,
     pub skill_points: SkillPoints,
     pub professions: Professions,
     pub dungeons: DungeonInfo,
     pub raids: RaidInfo,
-    pub quests: Set<String>,
+    /// the completed quests
+    pub quests: Set<Quest>,
+}
+
+bitflags::bitflags! {
+    /// The challenge gamemodes of a character, the api sends these as a list of names.
+    #[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, Default)]
+    pub struct Gamemodes: u8 {
+        const IRONMAN = 1;
+        const ULTIMATE_IRONMAN = 1 << 1;
+        const HARDCORE = 1 << 2;
+        const CRAFTSMAN = 1 << 3;
+        const HUNTED = 1 << 4;
+    }
+}
+
+impl Gamemodes {
+    /// The name the api uses for a single gamemode.
+    pub fn api_name(self) -> Option<&'static str> {
+        Some(match self {
+            Gamemodes::IRONMAN => "ironman",
+            Gamemodes::ULTIMATE_IRONMAN => "ultimate_ironman",
+            Gamemodes::HARDCORE => "hardcore",
+            Gamemodes::CRAFTSMAN => "craftsman",
+            Gamemodes::HUNTED => "hunted",
+            _ => return None,
+        })
+    }
+
+    pub fn from_api_name(name: &str) -> Option<Self> {
+        Gamemodes::all().iter().find(|g| g.api_name() == Some(name))
+    }
+
+    /// hardcore, ironman and craftsman
+    pub fn is_hic(self) -> bool {
+        self.contains(Gamemodes::HARDCORE | Gamemodes::IRONMAN | Gamemodes::CRAFTSMAN)
+    }
+
+    /// hardcore, ironman, craftsman and hunted
+    pub fn is_hich(self) -> bool {
+        self.is_hic() && self.contains(Gamemodes::HUNTED)
+    }
+
+    /// hardcore, ultimate ironman and craftsman
+    pub fn is_huic(self) -> bool {
+        self.contains(Gamemodes::HARDCORE | Gamemodes::ULTIMATE_IRONMAN | Gamemodes::CRAFTSMAN)
+    }
+
+    /// hardcore, ultimate ironman, craftsman and hunted
+    pub fn is_huich(self) -> bool {
+        self.is_huic() && self.contains(Gamemodes::HUNTED)
+    }
+
+    /// The combined challenge leaderboard a character with these gamemodes competes on, like
+    /// [`PlayerProfileLbType::HuicContent`], the most difficult one if multiple apply.
+    pub fn challenge_lb_type(self) -> Option<PlayerProfileLbType> {
+        if self.is_huich() {
+            Some(PlayerProfileLbType::HuichContent)
+        } else if self.is_huic() {
+            Some(PlayerProfileLbType::HuicContent)
+        } else if self.is_hich() {
+            Some(PlayerProfileLbType::HichContent)
+        } else if self.is_hic() {
+            Some(PlayerProfileLbType::HicContent)
+        } else {
+            None
+        }
+    }
+
+    /// All content leaderboards of single gamemodes and the challenge leaderboard a character with these gamemodes is on.
+    pub fn content_lb_types(self) -> Vec<PlayerProfileLbType> {
+        let single = [
+            (Gamemodes::IRONMAN, PlayerProfileLbType::IronmanContent),
+            (
+                Gamemodes::ULTIMATE_IRONMAN,
+                PlayerProfileLbType::UltimateIronmanContent,
+            ),
+            (Gamemodes::HARDCORE, PlayerProfileLbType::HardcoreContent),
+            (Gamemodes::CRAFTSMAN, PlayerProfileLbType::CraftsmanContent),
+            (Gamemodes::HUNTED, PlayerProfileLbType::HuntedContent),
+        ];
+        single
+            .into_iter()
+            .filter(|(gamemode, _)| self.contains(*gamemode))
+            .map(|(_, lb)| lb)
+            .chain(self.challenge_lb_type())
+            .collect()
+    }
+}
+
+impl Serialize for Gamemodes {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.iter().filter_map(Gamemodes::api_name))
+    }
+}
+
+impl<'de> Deserialize<'de> for Gamemodes {
+    /// unknown gamemodes are an error, unless decoding [leniently](crate::with_lenient), then they are skipped
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let names = Vec::<String>::deserialize(deserializer)?;
+        let mut gamemodes = Gamemodes::empty();
+        for name in names {
+            match Gamemodes::from_api_name(&name) {
+                Some(gamemode) => gamemodes |= gamemode,
+                None if is_lenient() => {}
+                None => {
+                    return Err(serde::de::Error::custom(format!(
+                        "unknown gamemode `{name}`"
+                    )))
+                }
+            }
+        }
+        Ok(gamemodes)
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
@@ -150,6 +550,12 @@ pub struct Professions {
     pub armouring: ProfessionInfo,
 }
 
+impl Character {
+    pub fn playtime_duration(&self) -> Duration {
+        hours(self.playtime)
+    }
+}
+
 impl Professions {
     pub fn get_profession(&self, profession: Profession) -> &ProfessionInfo {
         match profession {
@@ -181,7 +587,17 @@ pub struct ProfessionInfo {
 pub struct FullPlayerStats {
     #[serde(flatten)]
     pub player_stats: PlayerStats,
-    pub characters: Map<String, Character>,
+    pub characters: Map<CharacterUuid, Character>,
+}
+
+impl FullPlayerStats {
+    /// The playtime of all characters together.
+    pub fn character_playtime(&self) -> Duration {
+        self.characters
+            .values()
+            .map(Character::playtime_duration)
+            .sum()
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
@@ -195,7 +611,7 @@ pub struct CharacterInfo {
     /// always between 0 and 100 even when lvl 106
     pub xp_percent: u8,
     pub total_level: u64,
-    pub gamemode: Set<String>,
+    pub gamemode: Gamemodes,
     pub meta: Meta,
 }
 
@@ -211,6 +627,9 @@ pub enum SupportRank {
     VipPlus,
     Hero,
     Champion,
+    /// a value this crate doesn't know about, only decoded in [lenient](crate::with_lenient) mode
+    #[serde(untagged, deserialize_with = "deserialize_unknown")]
+    Unknown(String),
 }
 
 #[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
@@ -260,65 +679,182 @@ pub struct AttributeIcon {
     pub custom_model_data: String,
 }
 
+/// One of the players the api returns when a username belongs to multiple players.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PlayerCandidate {
+    /// the api uses this as the key of the candidate
+    pub uuid: PlayerUuid,
+    #[serde(alias = "storedName")]
+    pub username: String,
+    pub rank: Option<String>,
+    pub last_join: Option<String>,
+}
+
+impl PlayerCandidate {
+    /// When the player last joined, `None` if the api didn't send a valid date.
+    pub fn last_join_time(&self) -> Option<SystemTime> {
+        timestamp::to_system_time(self.last_join.as_deref()?)
+    }
+
+    /// Resolver that picks the player that joined most recently, players without a valid last join date come last.
+    pub fn most_recently_active(candidates: Vec<PlayerCandidate>) -> Option<PlayerCandidate> {
+        candidates
+            .into_iter()
+            .max_by_key(PlayerCandidate::last_join_time)
+    }
+}
+
+impl WynnApiError {
+    /// The players the api returned with [`WynnApiError::MultipleChoices`], players that can't be decoded are left out.
+    pub fn player_candidates(&self) -> Option<Vec<PlayerCandidate>> {
+        self.candidates()
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
 pub struct OnlinePlayerList {
     pub total: u64,
     pub players: Map<String, World>,
 }
 
-pub async fn player_main_stats(name_or_uuid: &str) -> Result<PlayerStats, WynnApiError> {
-    api_request(&format!("{API_LOCATION}/player/{name_or_uuid}")).await
+impl WynnClient {
+    pub async fn player_main_stats(
+        &self,
+        player: impl Into<PlayerRef>,
+    ) -> Result<PlayerStats, WynnApiError> {
+        self.api_request(&format!("/player/{}", player.into()))
+            .await
+    }
+
+    /// Like [`WynnClient::player_main_stats`], but when the name belongs to multiple players
+    /// `resolver` picks one of them, which is then requested by uuid.
+    ///
+    /// If `resolver` returns `None` the [`WynnApiError::MultipleChoices`] error is returned.
+    pub async fn player_main_stats_resolved(
+        &self,
+        player: impl Into<PlayerRef>,
+        resolver: impl FnOnce(Vec<PlayerCandidate>) -> Option<PlayerCandidate>,
+    ) -> Result<PlayerStats, WynnApiError> {
+        match self.player_main_stats(player).await {
+            Err(e @ WynnApiError::MultipleChoices(_)) => {
+                match e.player_candidates().and_then(resolver) {
+                    Some(candidate) => self.player_main_stats(candidate.uuid).await,
+                    None => Err(e),
+                }
+            }
+            result => result,
+        }
+    }
+
+    pub async fn player_full_stats(
+        &self,
+        player: impl Into<PlayerRef>,
+    ) -> Result<FullPlayerStats, WynnApiError> {
+        self.api_request(&format!("/player/{}?fullResult", player.into()))
+            .await
+    }
+
+    pub async fn player_character_list(
+        &self,
+        player: impl Into<PlayerRef>,
+    ) -> Result<Map<CharacterUuid, CharacterInfo>, WynnApiError> {
+        self.api_request(&format!("/player/{}/characters", player.into()))
+            .await
+    }
+
+    pub async fn player_character_data(
+        &self,
+        player: impl Into<PlayerRef>,
+        character: CharacterUuid,
+    ) -> Result<Character, WynnApiError> {
+        self.api_request(&format!("/player/{}/characters/{character}", player.into()))
+            .await
+    }
+
+    pub async fn player_character_abilities(
+        &self,
+        player: impl Into<PlayerRef>,
+        character: CharacterUuid,
+    ) -> Result<Vec<Ability>, WynnApiError> {
+        self.api_request(&format!(
+            "/player/{}/characters/{character}/abilities",
+            player.into()
+        ))
+        .await
+    }
+
+    pub async fn online_player_list(
+        &self,
+        identifier: Identifier,
+        servers: impl IntoIterator<Item = &World>,
+    ) -> Result<OnlinePlayerList, WynnApiError> {
+        let servers_vec = servers
+            .into_iter()
+            .map(|x| x.0.to_string())
+            .collect::<Vec<String>>();
+        let servers = servers_vec.join(",");
+        self.api_request(&format!("/player?identifier={identifier}&server={servers}"))
+            .await
+    }
+}
+
+pub async fn player_main_stats(player: impl Into<PlayerRef>) -> Result<PlayerStats, WynnApiError> {
+    WynnClient::global().player_main_stats(player).await
 }
 
-pub async fn player_full_stats(name_or_uuid: &str) -> Result<FullPlayerStats, WynnApiError> {
-    api_request(&format!("{API_LOCATION}/player/{name_or_uuid}?fullResult")).await
+pub async fn player_main_stats_resolved(
+    player: impl Into<PlayerRef>,
+    resolver: impl FnOnce(Vec<PlayerCandidate>) -> Option<PlayerCandidate>,
+) -> Result<PlayerStats, WynnApiError> {
+    WynnClient::global()
+        .player_main_stats_resolved(player, resolver)
+        .await
+}
+
+pub async fn player_full_stats(
+    player: impl Into<PlayerRef>,
+) -> Result<FullPlayerStats, WynnApiError> {
+    WynnClient::global().player_full_stats(player).await
 }
 
 pub async fn player_character_list(
-    name_or_uuid: &str,
-) -> Result<Map<String, CharacterInfo>, WynnApiError> {
-    api_request(&format!("{API_LOCATION}/player/{name_or_uuid}/characters")).await
+    player: impl Into<PlayerRef>,
+) -> Result<Map<CharacterUuid, CharacterInfo>, WynnApiError> {
+    WynnClient::global().player_character_list(player).await
 }
 
 pub async fn player_character_data(
-    name_or_uuid: &str,
-    character_uuid: &str,
+    player: impl Into<PlayerRef>,
+    character: CharacterUuid,
 ) -> Result<Character, WynnApiError> {
-    api_request(&format!(
-        "{API_LOCATION}/player/{name_or_uuid}/characters/{character_uuid}"
-    ))
-    .await
+    WynnClient::global()
+        .player_character_data(player, character)
+        .await
 }
 
 pub async fn player_character_abilities(
-    name_or_uuid: &str,
-    character_uuid: &str,
+    player: impl Into<PlayerRef>,
+    character: CharacterUuid,
 ) -> Result<Vec<Ability>, WynnApiError> {
-    api_request(&format!(
-        "{API_LOCATION}/player/{name_or_uuid}/characters/{character_uuid}/abilities"
-    ))
-    .await
+    WynnClient::global()
+        .player_character_abilities(player, character)
+        .await
 }
 
 pub async fn online_player_list(
     identifier: Identifier,
     servers: impl IntoIterator<Item = &World>,
 ) -> Result<OnlinePlayerList, WynnApiError> {
-    let servers_vec = servers
-        .into_iter()
-        .map(|x| x.0.to_string())
-        .collect::<Vec<String>>();
-    let servers = servers_vec.join(",");
-    api_request(&format!(
-        "{API_LOCATION}/player?identifier={identifier}&server={servers}"
-    ))
-    .await
+    WynnClient::global()
+        .online_player_list(identifier, servers)
+        .await
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use reqwest::StatusCode;
+    use crate::mock::{self, MockResponse, MockServer};
     use std::fmt::Debug;
     use tokio;
 
@@ -329,11 +865,7 @@ mod tests {
                 Ok(x) => {
                     dbg!(x);
                 }
-                Err(WynnApiError::ConnectionError(e)) => {
-                    if e.status() != Some(StatusCode::from_u16(500).unwrap()) {
-                        panic!("{}", e)
-                    }
-                }
+                Err(WynnApiError::ServerError { .. }) => {}
                 e => {
                     panic!("{:?}", e);
                 }
@@ -347,7 +879,7 @@ mod tests {
         verify(&full);
         let list = player_character_list(name).await;
         verify(&list);
-        let profile = "55a92635-0482-4e0a-b4ff-8284e7c8a326";
+        let profile = "55a92635-0482-4e0a-b4ff-8284e7c8a326".parse().unwrap();
         let data = player_character_data(name, profile).await;
         verify(&data);
         let abilities = player_character_abilities(name, profile).await;
@@ -362,4 +894,203 @@ mod tests {
         let uuids = online_player_list(Identifier::Uuid, &[World(1), World(5), World(8)]).await;
         assert!(uuids.is_ok());
     }
+
+    #[test]
+    fn dates_round_trip() {
+        let json = mock::player_stats("55a92635-0482-4e0a-b4ff-8284e7c8a326", "Bob");
+        let stats: PlayerStats = serde_json::from_str(&json).unwrap();
+        let value = serde_json::to_value(&stats).unwrap();
+        assert_eq!(value["firstJoin"], "2020-06-01T12:00:00.000Z");
+        assert_eq!(value["lastJoin"], "2024-09-01T18:30:00.000Z");
+        assert_eq!(serde_json::from_value::<PlayerStats>(value).unwrap(), stats);
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn date_accessors() {
+        use chrono::{TimeZone, Utc};
+
+        let json = mock::player_stats("55a92635-0482-4e0a-b4ff-8284e7c8a326", "Bob");
+        let mut stats: PlayerStats = serde_json::from_str(&json).unwrap();
+        assert_eq!(
+            stats.last_join_at(),
+            Ok(Utc.with_ymd_and_hms(2024, 9, 1, 18, 30, 0).unwrap())
+        );
+        stats.first_join = "a while ago".to_owned();
+        assert!(stats.first_join_at().is_err());
+    }
+
+    #[test]
+    fn content() {
+        let json = mock::player_stats("55a92635-0482-4e0a-b4ff-8284e7c8a326", "Bob");
+        let stats: PlayerStats = serde_json::from_str(&json).unwrap();
+        let dungeons = &stats.global_data.dungeons.list;
+        assert_eq!(dungeons[&Dungeon::DecrepitSewers], 3);
+        assert_eq!(dungeons[&Dungeon::CorruptedDecrepitSewers], 2);
+        assert_eq!(stats.global_data.raids.list[&Raid::NestOfTheGrootslangs], 3);
+
+        assert!(Dungeon::CorruptedSandSweptTomb.is_corrupted());
+        assert_eq!(
+            Dungeon::CorruptedSandSweptTomb.uncorrupted(),
+            Dungeon::SandSweptTomb
+        );
+        assert_eq!(
+            Dungeon::from("Nowhere"),
+            Dungeon::Other("Nowhere".to_owned())
+        );
+        assert_eq!(
+            Dungeon::Other("Nowhere".to_owned()).level_requirement(),
+            None
+        );
+        // the combat level requirements listed on the wiki
+        let levels = [
+            (Dungeon::DecrepitSewers, 10),
+            (Dungeon::UnderworldCrypt, 40),
+            (Dungeon::IceBarrows, 45),
+            (Dungeon::UndergrowthRuins, 55),
+            (Dungeon::GalleonsGraveyard, 65),
+            (Dungeon::EldritchOutlook, 100),
+            (Dungeon::CorruptedGalleonsGraveyard, 98),
+        ];
+        for (dungeon, level) in levels {
+            assert_eq!(dungeon.level_requirement(), Some(level), "{dungeon}");
+        }
+        assert_eq!(Raid::TheNamelessAnomaly.level_requirement(), Some(103));
+        assert!(Raid::ALL.iter().all(|raid| raid.player_lb_type().is_some()
+            && raid.guild_lb_type().is_some()
+            && raid.completion_lb_type().is_some()));
+        assert_eq!(
+            serde_json::to_string(&Raid::TheCanyonColossus).unwrap(),
+            r#""The Canyon Colossus""#
+        );
+
+        let quests: Set<Quest> =
+            serde_json::from_str(r#"["King's Recruit", "A Quest Of The Next Update"]"#).unwrap();
+        assert!(quests.contains(&Quest::KingsRecruit));
+        assert!(quests.contains(&Quest::Other("A Quest Of The Next Update".to_owned())));
+        assert_eq!(Quest::ArachnidsAscent.to_string(), "Arachnids' Ascent");
+    }
+
+    #[test]
+    fn gamemodes() {
+        let huich: Gamemodes =
+            serde_json::from_str(r#"["hardcore", "ultimate_ironman", "craftsman", "hunted"]"#)
+                .unwrap();
+        assert!(huich.is_huich() && huich.is_huic() && !huich.is_hic());
+        assert_eq!(
+            huich.challenge_lb_type(),
+            Some(PlayerProfileLbType::HuichContent)
+        );
+        assert_eq!(huich.content_lb_types().len(), 5);
+        let hic = Gamemodes::HARDCORE | Gamemodes::IRONMAN | Gamemodes::CRAFTSMAN;
+        assert_eq!(
+            hic.challenge_lb_type(),
+            Some(PlayerProfileLbType::HicContent)
+        );
+        assert_eq!(Gamemodes::HUNTED.challenge_lb_type(), None);
+        assert_eq!(
+            serde_json::to_string(&hic).unwrap(),
+            r#"["ironman","hardcore","craftsman"]"#
+        );
+
+        let unknown = r#"["ironman", "speedrun"]"#;
+        assert!(serde_json::from_str::<Gamemodes>(unknown).is_err());
+        let lenient = crate::with_lenient(true, || serde_json::from_str::<Gamemodes>(unknown));
+        assert_eq!(lenient.unwrap(), Gamemodes::IRONMAN);
+    }
+
+    #[test]
+    fn playtime() {
+        assert_eq!(hours(1.5), Duration::from_secs(5400));
+        assert_eq!(hours(-1.0), Duration::ZERO);
+        assert_eq!(format_playtime(hours(123.0 * 24.0 + 4.5)), "123d 4h");
+        assert_eq!(format_playtime(hours(4.5)), "4h 30m");
+        assert_eq!(format_playtime(Duration::from_secs(59)), "0m");
+    }
+
+    #[tokio::test]
+    async fn resolve_multiple_choices() {
+        let server = MockServer::start(|request| match request.path.as_str() {
+            "/player/Bob" => MockResponse::status(
+                300,
+                r#"{
+                    "9e42a1b2-1c5f-4bd4-9c4f-0c8e9e0f5a11": {"storedName": "Bob", "rank": "Player", "lastJoin": "2024-01-01T10:00:00.000Z"},
+                    "4f9c8b3e-5d8a-4b2e-8a9e-2f1d3c6b7a22": {"storedName": "Bob", "rank": "Player", "lastJoin": "2024-01-01 18:30:00"},
+                    "0b1c2d3e-4f50-4617-8293-a4b5c6d7e8f9": {"storedName": "Bob", "lastJoin": "yesterday"},
+                    "not a uuid": {"storedName": "Bob"}
+                }"#,
+            ),
+            path => {
+                let uuid = path.trim_start_matches("/player/");
+                MockResponse::json(mock::player_stats(uuid, "Bob"))
+            }
+        })
+        .await;
+        let client = WynnClient::builder()
+            .base_url(server.url())
+            .build()
+            .unwrap();
+
+        let error = client.player_main_stats("Bob").await.unwrap_err();
+        let mut candidates = error.player_candidates().unwrap();
+        candidates.sort_by_key(|c| c.uuid);
+        // the candidate with an invalid uuid is left out, the others are still there
+        assert_eq!(candidates.len(), 3);
+        assert_eq!(candidates[1].username, "Bob");
+        assert_eq!(
+            candidates[1].last_join.as_deref(),
+            Some("2024-01-01 18:30:00")
+        );
+        assert_eq!(candidates[0].last_join_time(), None);
+
+        let stats = client
+            .player_main_stats_resolved("Bob", PlayerCandidate::most_recently_active)
+            .await
+            .unwrap();
+        assert_eq!(
+            stats.uuid.to_string(),
+            "4f9c8b3e-5d8a-4b2e-8a9e-2f1d3c6b7a22"
+        );
+
+        let refused = client.player_main_stats_resolved("Bob", |_| None).await;
+        assert!(matches!(refused, Err(WynnApiError::MultipleChoices(_))));
+    }
+
+    #[test]
+    fn rankings() {
+        let json = mock::player_stats("55a92635-0482-4e0a-b4ff-8284e7c8a326", "Bob");
+        let mut stats: PlayerStats = serde_json::from_str(&json).unwrap();
+        let rankings: Vec<_> = stats.rankings().collect();
+        assert_eq!(
+            rankings,
+            [
+                (
+                    LeaderboardKind::PlayerGlobal(PlayerGlobalLbType::CombatGlobalLevel),
+                    1000,
+                    Some(990),
+                    Some(-10)
+                ),
+                (
+                    LeaderboardKind::PlayerGlobal(PlayerGlobalLbType::NogCompletion),
+                    42,
+                    Some(45),
+                    Some(3)
+                ),
+            ]
+        );
+        assert_eq!(
+            stats.ranking_of(PlayerGlobalLbType::NogCompletion),
+            Some(42)
+        );
+        assert_eq!(stats.ranking_of(PlayerGlobalLbType::TnaCompletion), None);
+        assert_eq!(
+            stats.unknown_rankings().collect::<Vec<_>>(),
+            [("someNewBoard", 7)]
+        );
+
+        // like on the leaderboards, a previous rank of 0 means there was none
+        stats.previous_ranking.insert("nogCompletion".to_owned(), 0);
+        let (_, rank, previous, delta) = stats.rankings().nth(1).unwrap();
+        assert_eq!((rank, previous, delta), (42, None, None));
+    }
 }
@@ -0,0 +1,343 @@
+use crate::Map;
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+use std::{
+    sync::Mutex,
+    time::{Duration, Instant, SystemTime},
+};
+use tokio::sync::Notify;
+
+pub const REMAINING_HEADER: &str = "ratelimit-remaining";
+pub const RESET_HEADER: &str = "ratelimit-reset";
+
+/// how long to wait when the api says no requests are left without saying when that resets
+const DEFAULT_WINDOW: Duration = Duration::from_secs(1);
+/// how long other requests wait for the first request of a group before sending anyway
+const PROBE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The groups the api uses to budget requests, every group has its own limit.
+#[derive(Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Clone, Copy)]
+pub enum RouteGroup {
+    Ability,
+    Classes,
+    Guild,
+    Item,
+    Leaderboard,
+    Map,
+    News,
+    Player,
+    Search,
+    Other,
+}
+
+impl RouteGroup {
+    /// `path` is relative to the base url, for example `/player/de_grote`
+    pub fn from_path(path: &str) -> Self {
+        let segment = path
+            .trim_start_matches('/')
+            .split(['/', '?'])
+            .next()
+            .unwrap_or_default();
+        match segment {
+            "ability" => RouteGroup::Ability,
+            "classes" => RouteGroup::Classes,
+            "guild" => RouteGroup::Guild,
+            "item" => RouteGroup::Item,
+            "leaderboards" => RouteGroup::Leaderboard,
+            "map" => RouteGroup::Map,
+            "latest-news" => RouteGroup::News,
+            "player" => RouteGroup::Player,
+            "search" => RouteGroup::Search,
+            _ => RouteGroup::Other,
+        }
+    }
+}
+
+/// A fixed budget of requests, used until the api tells us the real one.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct GroupLimit {
+    pub requests: u32,
+    pub per: Duration,
+}
+
+#[derive(Debug, Default)]
+struct Bucket {
+    limit: Option<GroupLimit>,
+    remaining: Option<u32>,
+    reset_at: Option<Instant>,
+    /// whether a response of this group has been seen, before that only one request is sent at a time
+    probed: bool,
+    /// when the request that is waiting for the first response was sent
+    probing_since: Option<Instant>,
+}
+
+impl Bucket {
+    /// takes one request out of the budget, or returns how long to wait for the next window
+    fn take(&mut self, now: Instant) -> Result<(), Duration> {
+        if self.reset_at.is_some_and(|reset_at| reset_at <= now) {
+            self.reset_at = None;
+            self.remaining = None;
+        }
+        if self.remaining.is_none() {
+            self.start_window(now);
+        }
+        match (self.remaining, self.reset_at) {
+            (Some(0), Some(reset_at)) => Err(reset_at - now),
+            (Some(0), None) => {
+                self.reset_at = Some(now + DEFAULT_WINDOW);
+                Err(DEFAULT_WINDOW)
+            }
+            (Some(remaining), _) => {
+                self.remaining = Some(remaining.saturating_sub(1));
+                Ok(())
+            }
+            (None, _) if self.probed => Ok(()),
+            (None, _) => match self.probing_since {
+                Some(since) if now < since + PROBE_TIMEOUT => Err(since + PROBE_TIMEOUT - now),
+                _ => {
+                    self.probing_since = Some(now);
+                    Ok(())
+                }
+            },
+        }
+    }
+
+    fn start_window(&mut self, now: Instant) {
+        if let Some(limit) = self.limit {
+            self.remaining = Some(limit.requests);
+            self.reset_at = Some(now + limit.per);
+        }
+    }
+}
+
+/// Client side rate limiter that keeps track of the `ratelimit-*` headers the api sends.
+///
+/// When the budget of a [`RouteGroup`] runs out, requests to that group wait until the window resets.
+/// Until the first response of a group without a configured budget arrives, only one request to it is sent at a time,
+/// so a burst of requests can't go out before the limiter knows the budget.
+#[derive(Debug, Default)]
+pub struct RateLimiter {
+    buckets: Mutex<Map<RouteGroup, Bucket>>,
+    /// woken when a response updates a bucket
+    updated: Notify,
+}
+
+impl RateLimiter {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets a budget for a group that is used before any headers have been received.
+    pub fn with_limit(self, group: RouteGroup, requests: u32, per: Duration) -> Self {
+        self.buckets
+            .lock()
+            .expect("ratelimiter lock poisoned")
+            .entry(group)
+            .or_default()
+            .limit = Some(GroupLimit { requests, per });
+        self
+    }
+
+    /// The amount of requests left in the current window, if known.
+    pub fn remaining(&self, group: RouteGroup) -> Option<u32> {
+        self.buckets
+            .lock()
+            .expect("ratelimiter lock poisoned")
+            .get(&group)
+            .and_then(|b| b.remaining)
+    }
+
+    /// Waits until a request to `group` is allowed.
+    ///
+    /// Every request has to be followed by [`update`](Self::update) with its response, or [`cancel`](Self::cancel)
+    /// if there is none.
+    pub async fn acquire(&self, group: RouteGroup) {
+        loop {
+            let updated = self.updated.notified();
+            tokio::pin!(updated);
+            let wait = {
+                let mut buckets = self.buckets.lock().expect("ratelimiter lock poisoned");
+                updated.as_mut().enable();
+                match buckets.entry(group).or_default().take(Instant::now()) {
+                    Ok(()) => return,
+                    Err(wait) => wait,
+                }
+            };
+            let _ = tokio::time::timeout(wait, updated).await;
+        }
+    }
+
+    /// A request to `group` didn't get a response, lets the next request find out the budget.
+    pub fn cancel(&self, group: RouteGroup) {
+        let mut buckets = self.buckets.lock().expect("ratelimiter lock poisoned");
+        buckets.entry(group).or_default().probing_since = None;
+        self.updated.notify_waiters();
+    }
+
+    /// Updates the budget of `group` with the headers of a response.
+    pub fn update(&self, group: RouteGroup, status: u16, headers: &HeaderMap) {
+        let header = |name| {
+            headers
+                .get(name)
+                .and_then(|v| v.to_str().ok())
+                .and_then(|v| v.trim().parse::<u64>().ok())
+        };
+        let now = Instant::now();
+        let mut buckets = self.buckets.lock().expect("ratelimiter lock poisoned");
+        let bucket = buckets.entry(group).or_default();
+        bucket.probed = true;
+        bucket.probing_since = None;
+        if let Some(remaining) = header(REMAINING_HEADER) {
+            bucket.remaining = Some(u32::try_from(remaining).unwrap_or(u32::MAX));
+        }
+        if let Some(reset) = header(RESET_HEADER) {
+            bucket.reset_at = Some(now + Duration::from_secs(reset));
+        }
+        if status == 429 {
+            bucket.remaining = Some(0);
+            if let Some(retry_after) = retry_after(headers) {
+                bucket.reset_at = Some(now + retry_after);
+            } else if bucket.reset_at.is_none() {
+                bucket.reset_at = Some(now + DEFAULT_WINDOW);
+            }
+        }
+        self.updated.notify_waiters();
+    }
+}
+
+/// Parses the `Retry-After` header, which is either an amount of seconds or a date.
+pub fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let value = headers.get(RETRY_AFTER)?.to_str().ok()?.trim();
+    if let Ok(secs) = value.parse::<u64>() {
+        return Some(Duration::from_secs(secs));
+    }
+    let date = httpdate::parse_http_date(value).ok()?;
+    Some(date.duration_since(SystemTime::now()).unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        mock::{MockResponse, MockServer},
+        Identifier, WynnClient,
+    };
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn route_groups() {
+        assert_eq!(
+            RouteGroup::from_path("/player/de_grote"),
+            RouteGroup::Player
+        );
+        assert_eq!(
+            RouteGroup::from_path("/player?server=1"),
+            RouteGroup::Player
+        );
+        assert_eq!(RouteGroup::from_path("/latest-news"), RouteGroup::News);
+        assert_eq!(
+            RouteGroup::from_path("/leaderboards/guildLevel"),
+            RouteGroup::Leaderboard
+        );
+        assert_eq!(RouteGroup::from_path("/unknown"), RouteGroup::Other);
+    }
+
+    #[tokio::test]
+    async fn fixed_limit() {
+        let limiter =
+            RateLimiter::new().with_limit(RouteGroup::Item, 2, Duration::from_millis(300));
+        let start = Instant::now();
+        for _ in 0..3 {
+            limiter.acquire(RouteGroup::Item).await;
+        }
+        assert!(start.elapsed() >= Duration::from_millis(300));
+        // other groups are not limited by the item budget
+        let start = Instant::now();
+        limiter.acquire(RouteGroup::Player).await;
+        assert!(start.elapsed() < Duration::from_millis(100));
+    }
+
+    #[tokio::test]
+    async fn no_requests_left() {
+        let limiter = RateLimiter::new();
+        let mut headers = HeaderMap::new();
+        headers.insert(REMAINING_HEADER, 0.into());
+        limiter.update(RouteGroup::Item, 200, &headers);
+        // no reset header, so it waits for the default window
+        let start = Instant::now();
+        limiter.acquire(RouteGroup::Item).await;
+        assert!(start.elapsed() >= DEFAULT_WINDOW);
+
+        // more than fits in a u32 is not zero
+        headers.insert(REMAINING_HEADER, (1u64 << 32).into());
+        limiter.update(RouteGroup::Item, 200, &headers);
+        assert_eq!(limiter.remaining(RouteGroup::Item), Some(u32::MAX));
+    }
+
+    #[tokio::test]
+    async fn first_request_alone() {
+        let limiter = RateLimiter::new();
+        limiter.acquire(RouteGroup::Guild).await;
+        // the budget isn't known yet, so the second request waits for the first response
+        let second = tokio::time::timeout(
+            Duration::from_millis(100),
+            limiter.acquire(RouteGroup::Guild),
+        );
+        assert!(second.await.is_err());
+        limiter.acquire(RouteGroup::Player).await;
+
+        limiter.update(RouteGroup::Guild, 200, &HeaderMap::new());
+        let start = Instant::now();
+        for _ in 0..3 {
+            limiter.acquire(RouteGroup::Guild).await;
+        }
+        assert!(start.elapsed() < Duration::from_millis(100));
+
+        // a request without a response lets the next one through
+        limiter.acquire(RouteGroup::News).await;
+        limiter.cancel(RouteGroup::News);
+        let next = tokio::time::timeout(
+            Duration::from_millis(100),
+            limiter.acquire(RouteGroup::News),
+        );
+        assert!(next.await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn honours_headers() {
+        let player_requests = AtomicUsize::new(0);
+        let server = MockServer::start(move |request| {
+            if request.path.starts_with("/guild") {
+                return MockResponse::json("{}");
+            }
+            let n = player_requests.fetch_add(1, Ordering::SeqCst);
+            MockResponse::json(r#"{"total": 0, "players": {}}"#)
+                .with_header(REMAINING_HEADER, 1usize.saturating_sub(n))
+                .with_header(RESET_HEADER, 1)
+        })
+        .await;
+        let client = WynnClient::builder()
+            .base_url(server.url())
+            .build()
+            .unwrap();
+        let start = Instant::now();
+        for _ in 0..2 {
+            client
+                .online_player_list(Identifier::Username, &[])
+                .await
+                .unwrap();
+        }
+        let limiter = client.rate_limiter().unwrap();
+        assert_eq!(limiter.remaining(RouteGroup::Player), Some(0));
+        // other groups still have budget left
+        client.guild_teritories().await.unwrap();
+        assert!(start.elapsed() < Duration::from_millis(900));
+        // the player budget is used up, so this one has to wait for the reset
+        client
+            .online_player_list(Identifier::Username, &[])
+            .await
+            .unwrap();
+        assert!(start.elapsed() >= Duration::from_millis(900));
+        assert_eq!(server.request_count(), 4);
+    }
+}
@@ -0,0 +1,207 @@
+use crate::{Set, WynnApiError};
+use std::{
+    collections::hash_map::RandomState,
+    hash::{BuildHasher, Hasher},
+    time::Duration,
+};
+
+/// When and how often a failed request is sent again.
+///
+/// The delay between attempts doubles every attempt, starting at `base_delay` up to `max_delay`,
+/// unless the api sent a `Retry-After` header, then that is used instead (also at most `max_delay`).
+#[derive(Debug, PartialEq, Clone)]
+pub struct RetryPolicy {
+    /// the total amount of times a request is sent, including the first one
+    pub max_attempts: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+    /// the fraction of the delay that is randomized, between 0 and 1
+    pub jitter: f64,
+    pub retry_statuses: Set<u16>,
+    pub retry_timeouts: bool,
+    pub retry_transport: bool,
+}
+
+impl Default for RetryPolicy {
+    /// 3 attempts that retry on timeouts, connection errors and the status codes 429, 500, 502, 503 and 504.
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(30),
+            jitter: 0.5,
+            retry_statuses: [429, 500, 502, 503, 504].into_iter().collect(),
+            retry_timeouts: true,
+            retry_transport: true,
+        }
+    }
+}
+
+impl RetryPolicy {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sends every request only once.
+    pub fn never() -> Self {
+        Self {
+            max_attempts: 1,
+            ..Self::default()
+        }
+    }
+
+    pub fn max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts.max(1);
+        self
+    }
+
+    pub fn base_delay(mut self, delay: Duration) -> Self {
+        self.base_delay = delay;
+        self
+    }
+
+    pub fn max_delay(mut self, delay: Duration) -> Self {
+        self.max_delay = delay;
+        self
+    }
+
+    pub fn jitter(mut self, jitter: f64) -> Self {
+        self.jitter = jitter.clamp(0.0, 1.0);
+        self
+    }
+
+    pub fn retry_statuses(mut self, statuses: impl IntoIterator<Item = u16>) -> Self {
+        self.retry_statuses = statuses.into_iter().collect();
+        self
+    }
+
+    pub fn retry_timeouts(mut self, retry: bool) -> Self {
+        self.retry_timeouts = retry;
+        self
+    }
+
+    pub fn retry_transport(mut self, retry: bool) -> Self {
+        self.retry_transport = retry;
+        self
+    }
+
+    /// Whether a request that failed with `error` should be sent again.
+    pub fn should_retry(&self, error: &WynnApiError) -> bool {
+        match error {
+            WynnApiError::Timeout(_) => self.retry_timeouts,
+            WynnApiError::Transport(_) => self.retry_transport && error.is_retryable(),
+            // multiple choices isn't really an error of the api
+            WynnApiError::MultipleChoices(_) => false,
+            _ => error
+                .status()
+                .is_some_and(|status| self.retry_statuses.contains(&status)),
+        }
+    }
+
+    /// How long to wait after the `attempt`th attempt failed with `error`.
+    pub fn delay(&self, attempt: u32, error: &WynnApiError) -> Duration {
+        if let Some(retry_after) = error.retry_after() {
+            return retry_after.min(self.max_delay);
+        }
+        let backoff = self
+            .base_delay
+            .saturating_mul(2u32.saturating_pow(attempt.saturating_sub(1)))
+            .min(self.max_delay);
+        // a random number between 0 and 1, good enough to spread out retries
+        let random = RandomState::new().build_hasher().finish() as f64 / u64::MAX as f64;
+        backoff.mul_f64(1.0 - self.jitter * random)
+    }
+}
+
+/// Information about how a request was answered, see [`WynnClient::request_with_meta`](crate::WynnClient::request_with_meta).
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct ResponseMeta {
+    pub url: String,
+    /// how many times the request was sent, 0 if it was answered from the cache
+    pub attempts: u32,
+    /// whether the response came from the cache or the store
+    pub from_cache: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        mock::{MockResponse, MockServer},
+        news::NewsArticle,
+        WynnClient,
+    };
+    use std::{
+        sync::atomic::{AtomicUsize, Ordering},
+        time::Instant,
+    };
+
+    fn server_error() -> WynnApiError {
+        WynnApiError::from_status(500, "", None, None)
+    }
+
+    #[test]
+    fn backoff() {
+        let policy = RetryPolicy::new()
+            .base_delay(Duration::from_secs(1))
+            .max_delay(Duration::from_secs(3))
+            .jitter(0.0);
+        assert_eq!(policy.delay(1, &server_error()), Duration::from_secs(1));
+        assert_eq!(policy.delay(2, &server_error()), Duration::from_secs(2));
+        assert_eq!(policy.delay(3, &server_error()), Duration::from_secs(3));
+        let limited = WynnApiError::from_status(429, "", Some(Duration::from_secs(2)), None);
+        assert_eq!(policy.delay(1, &limited), Duration::from_secs(2));
+        // a long retry-after is capped too
+        let limited = WynnApiError::from_status(429, "", Some(Duration::from_secs(3600)), None);
+        assert_eq!(policy.delay(1, &limited), Duration::from_secs(3));
+
+        let jittered = policy.jitter(0.5).delay(2, &server_error());
+        assert!(jittered >= Duration::from_secs(1) && jittered <= Duration::from_secs(2));
+    }
+
+    #[test]
+    fn retryable() {
+        let policy = RetryPolicy::new().retry_statuses([503]);
+        assert!(!policy.should_retry(&server_error()));
+        assert!(policy.should_retry(&WynnApiError::from_status(503, "", None, None)));
+        assert!(!policy.should_retry(&WynnApiError::from_status(404, "", None, None)));
+    }
+
+    #[tokio::test]
+    async fn retries_requests() {
+        let requests = AtomicUsize::new(0);
+        let server = MockServer::start(move |request| {
+            if request.path == "/latest-news" && requests.fetch_add(1, Ordering::SeqCst) < 2 {
+                return MockResponse::status(500, "");
+            }
+            if request.path == "/map/quests" {
+                return MockResponse::status(503, "").with_header("Retry-After", 1);
+            }
+            MockResponse::json("[]")
+        })
+        .await;
+        let policy = RetryPolicy::new()
+            .max_attempts(3)
+            .base_delay(Duration::from_millis(10));
+        let client = WynnClient::builder()
+            .base_url(server.url())
+            .retry_policy(policy)
+            .build()
+            .unwrap();
+        let (news, meta) = client
+            .request_with_meta::<Vec<NewsArticle>>("/latest-news")
+            .await
+            .unwrap();
+        assert!(news.is_empty());
+        assert_eq!(meta.attempts, 3);
+        assert!(!meta.from_cache);
+
+        // a policy for just this call
+        let start = Instant::now();
+        let once = client.with_retry(RetryPolicy::new().max_attempts(2));
+        assert!(once.quest_count().await.is_err());
+        assert!(start.elapsed() >= Duration::from_secs(1));
+        assert_eq!(server.request_count(), 5);
+    }
+}
@@ -1,6 +1,6 @@
 use crate::{
-    api_request, deserialize_with_default, guild::TerritoryLocation, item::Item, Map, WynnApiError,
-    API_LOCATION,
+    deserialize_with_default, geometry::Rect, guild::TerritoryLocation, item::Item, Map,
+    WynnApiError, WynnClient,
 };
 use serde::{Deserialize, Serialize};
 
@@ -34,8 +34,27 @@ pub struct DiscoveryLocation {
     pub end: (i64, i64, i64),
 }
 
+impl DiscoveryLocation {
+    /// The discovery as a rectangle on the map, without the height.
+    pub fn rect(&self) -> Rect {
+        Rect::new((self.start.0, self.start.2), (self.end.0, self.end.2))
+    }
+
+    /// Whether the block is inside the discovery, the `max` edges are excluded like in [`Rect`].
+    pub fn contains(&self, x: i64, y: i64, z: i64) -> bool {
+        let (min_y, max_y) = (self.start.1.min(self.end.1), self.start.1.max(self.end.1));
+        self.rect().contains(x, z) && (min_y..max_y).contains(&y)
+    }
+}
+
+impl WynnClient {
+    pub async fn search(&self, query: &str) -> Result<SearchResult, WynnApiError> {
+        self.api_request(&format!("/search/{query}")).await
+    }
+}
+
 pub async fn search(query: &str) -> Result<SearchResult, WynnApiError> {
-    api_request(&format!("{API_LOCATION}/search/{query}")).await
+    WynnClient::global().search(query).await
 }
 
 #[cfg(test)]
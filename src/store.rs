@@ -0,0 +1,408 @@
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt::Debug,
+    fs, io,
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicU64, Ordering},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use thiserror::Error;
+
+/// A response that is saved in a [`CacheStore`].
+#[derive(Debug, PartialEq, Clone)]
+pub struct StoredResponse {
+    pub url: String,
+    pub body: String,
+    pub fetched_at: SystemTime,
+    /// until when the api said the response is fresh, `None` if it shouldn't be cached
+    pub expires_at: Option<SystemTime>,
+    /// the [`API_VERSION`](crate::API_VERSION) of the crate that fetched the response
+    pub api_version: String,
+}
+
+impl StoredResponse {
+    pub fn is_fresh(&self) -> bool {
+        self.expires_at.is_some_and(|e| e > SystemTime::now())
+    }
+
+    pub fn age(&self) -> Duration {
+        SystemTime::now()
+            .duration_since(self.fetched_at)
+            .unwrap_or_default()
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum StoreError {
+    #[error("couldn't access the cache store")]
+    Io(#[from] io::Error),
+    #[error("the cache store contains invalid data")]
+    Corrupted(#[from] serde_json::Error),
+    #[cfg(feature = "sqlite")]
+    #[error("couldn't access the sqlite cache store")]
+    Sqlite(#[from] rusqlite::Error),
+}
+
+/// Persistent storage for responses, so they can be reused after a restart or while offline.
+pub trait CacheStore: Debug + Send + Sync {
+    fn load(&self, url: &str) -> Result<Option<StoredResponse>, StoreError>;
+    fn save(&self, response: &StoredResponse) -> Result<(), StoreError>;
+    fn remove(&self, url: &str) -> Result<(), StoreError>;
+    fn clear(&self) -> Result<(), StoreError>;
+}
+
+fn to_secs(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+fn from_secs(secs: u64) -> SystemTime {
+    UNIX_EPOCH + Duration::from_secs(secs)
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct FileEntry {
+    url: String,
+    fetched_at: u64,
+    expires_at: Option<u64>,
+    api_version: String,
+    body: String,
+}
+
+/// Replaces the file at `path` with `contents`, readers see either the old or the new file but never half of one.
+///
+/// The contents are written to a temporary file next to `path` first, with a name no other write uses at the same time.
+pub(crate) fn write_atomic(path: &Path, contents: impl AsRef<[u8]>) -> io::Result<()> {
+    static WRITES: AtomicU64 = AtomicU64::new(0);
+    let mut temp = path.as_os_str().to_owned();
+    temp.push(format!(
+        ".{}-{}.tmp",
+        std::process::id(),
+        WRITES.fetch_add(1, Ordering::Relaxed)
+    ));
+    let result = fs::write(&temp, contents).and_then(|()| fs::rename(&temp, path));
+    if result.is_err() {
+        let _ = fs::remove_file(&temp);
+    }
+    result
+}
+
+/// Stores every response as a json file in a directory.
+#[derive(Debug, Clone)]
+pub struct FileStore {
+    dir: PathBuf,
+}
+
+impl FileStore {
+    /// Creates the directory if it doesn't exist yet.
+    pub fn new(dir: impl Into<PathBuf>) -> Result<Self, StoreError> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+        Ok(Self { dir })
+    }
+
+    fn path(&self, url: &str) -> PathBuf {
+        // fnv-1a, because the hashers of std aren't stable between releases
+        let hash = url.bytes().fold(0xcbf29ce484222325u64, |hash, byte| {
+            (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+        });
+        self.dir.join(format!("{hash:016x}.json"))
+    }
+}
+
+impl CacheStore for FileStore {
+    fn load(&self, url: &str) -> Result<Option<StoredResponse>, StoreError> {
+        let text = match fs::read_to_string(self.path(url)) {
+            Ok(text) => text,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+        let entry: FileEntry = serde_json::from_str(&text)?;
+        // another url with the same hash
+        if entry.url != url {
+            return Ok(None);
+        }
+        Ok(Some(StoredResponse {
+            url: entry.url,
+            body: entry.body,
+            fetched_at: from_secs(entry.fetched_at),
+            expires_at: entry.expires_at.map(from_secs),
+            api_version: entry.api_version,
+        }))
+    }
+
+    fn save(&self, response: &StoredResponse) -> Result<(), StoreError> {
+        let entry = FileEntry {
+            url: response.url.clone(),
+            fetched_at: to_secs(response.fetched_at),
+            expires_at: response.expires_at.map(to_secs),
+            api_version: response.api_version.clone(),
+            body: response.body.clone(),
+        };
+        write_atomic(&self.path(&response.url), serde_json::to_string(&entry)?)?;
+        Ok(())
+    }
+
+    fn remove(&self, url: &str) -> Result<(), StoreError> {
+        match fs::remove_file(self.path(url)) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+
+    fn clear(&self) -> Result<(), StoreError> {
+        for file in fs::read_dir(&self.dir)? {
+            let path = file?.path();
+            if path.extension().is_some_and(|e| e == "json") {
+                fs::remove_file(path)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Stores every response in a table of a sqlite database.
+#[cfg(feature = "sqlite")]
+#[derive(Debug)]
+pub struct SqliteStore {
+    connection: std::sync::Mutex<rusqlite::Connection>,
+}
+
+#[cfg(feature = "sqlite")]
+impl SqliteStore {
+    pub fn open(path: impl AsRef<std::path::Path>) -> Result<Self, StoreError> {
+        Self::from_connection(rusqlite::Connection::open(path)?)
+    }
+
+    pub fn in_memory() -> Result<Self, StoreError> {
+        Self::from_connection(rusqlite::Connection::open_in_memory()?)
+    }
+
+    /// Creates the `responses` table if it doesn't exist yet.
+    pub fn from_connection(connection: rusqlite::Connection) -> Result<Self, StoreError> {
+        connection.execute(
+            "CREATE TABLE IF NOT EXISTS responses (
+                url TEXT PRIMARY KEY,
+                fetched_at INTEGER NOT NULL,
+                expires_at INTEGER,
+                api_version TEXT NOT NULL,
+                body TEXT NOT NULL
+            )",
+            (),
+        )?;
+        Ok(Self {
+            connection: std::sync::Mutex::new(connection),
+        })
+    }
+
+    fn connection(&self) -> std::sync::MutexGuard<'_, rusqlite::Connection> {
+        self.connection.lock().expect("sqlite lock poisoned")
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl CacheStore for SqliteStore {
+    fn load(&self, url: &str) -> Result<Option<StoredResponse>, StoreError> {
+        use rusqlite::OptionalExtension;
+
+        let response = self
+            .connection()
+            .query_row(
+                "SELECT url, fetched_at, expires_at, api_version, body FROM responses WHERE url = ?1",
+                [url],
+                |row| {
+                    Ok(StoredResponse {
+                        url: row.get(0)?,
+                        fetched_at: from_secs(row.get(1)?),
+                        expires_at: row.get::<_, Option<u64>>(2)?.map(from_secs),
+                        api_version: row.get(3)?,
+                        body: row.get(4)?,
+                    })
+                },
+            )
+            .optional()?;
+        Ok(response)
+    }
+
+    fn save(&self, response: &StoredResponse) -> Result<(), StoreError> {
+        self.connection().execute(
+            "INSERT OR REPLACE INTO responses (url, fetched_at, expires_at, api_version, body)
+            VALUES (?1, ?2, ?3, ?4, ?5)",
+            (
+                &response.url,
+                to_secs(response.fetched_at),
+                response.expires_at.map(to_secs),
+                &response.api_version,
+                &response.body,
+            ),
+        )?;
+        Ok(())
+    }
+
+    fn remove(&self, url: &str) -> Result<(), StoreError> {
+        self.connection()
+            .execute("DELETE FROM responses WHERE url = ?1", [url])?;
+        Ok(())
+    }
+
+    fn clear(&self) -> Result<(), StoreError> {
+        self.connection().execute("DELETE FROM responses", ())?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        mock::{MockResponse, MockServer},
+        WynnApiError, WynnClient, API_VERSION,
+    };
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("wynncraft_api_{name}_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn check_store(store: &dyn CacheStore) {
+        let response = StoredResponse {
+            url: "https://api.wynncraft.com/v3/guild/list/guild".to_owned(),
+            body: "{}".to_owned(),
+            fetched_at: from_secs(1_700_000_000),
+            expires_at: None,
+            api_version: API_VERSION.to_owned(),
+        };
+        assert_eq!(store.load(&response.url).unwrap(), None);
+        store.save(&response).unwrap();
+        assert_eq!(store.load(&response.url).unwrap(), Some(response.clone()));
+        assert!(!response.is_fresh());
+        store.remove(&response.url).unwrap();
+        assert_eq!(store.load(&response.url).unwrap(), None);
+        store.save(&response).unwrap();
+        store.clear().unwrap();
+        assert_eq!(store.load(&response.url).unwrap(), None);
+    }
+
+    #[test]
+    fn file_store() {
+        let dir = temp_dir("file_store");
+        check_store(&FileStore::new(&dir).unwrap());
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn concurrent_saves() {
+        let dir = temp_dir("concurrent_saves");
+        let store = FileStore::new(&dir).unwrap();
+        std::thread::scope(|scope| {
+            for i in 0..8 {
+                let store = &store;
+                scope.spawn(move || {
+                    for _ in 0..20 {
+                        store
+                            .save(&StoredResponse {
+                                url: "https://api.wynncraft.com/v3/guild/list/guild".to_owned(),
+                                body: format!("{{\"thread\": {i}}}"),
+                                fetched_at: from_secs(1_700_000_000),
+                                expires_at: None,
+                                api_version: API_VERSION.to_owned(),
+                            })
+                            .unwrap();
+                    }
+                });
+            }
+        });
+        assert!(store
+            .load("https://api.wynncraft.com/v3/guild/list/guild")
+            .unwrap()
+            .is_some());
+        // no temporary files are left behind
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 1);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn store_errors() {
+        let dir = temp_dir("store_errors");
+        let server = MockServer::start(|request| {
+            let response = MockResponse::json("{}");
+            if request.path == "/guild/list/territory" {
+                response.with_header("Cache-Control", "no-store")
+            } else {
+                response
+            }
+        })
+        .await;
+        let client = WynnClient::builder()
+            .base_url(server.url())
+            .store(FileStore::new(&dir).unwrap())
+            .build()
+            .unwrap();
+        client.guild_teritories().await.unwrap();
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 0);
+
+        // a corrupt file is a miss online, but an error offline
+        client.guild_list().await.unwrap();
+        for file in fs::read_dir(&dir).unwrap() {
+            fs::write(file.unwrap().path(), "not json").unwrap();
+        }
+        assert!(client.guild_list().await.unwrap().is_empty());
+        assert_eq!(server.request_count(), 3);
+        fs::write(
+            fs::read_dir(&dir).unwrap().next().unwrap().unwrap().path(),
+            "not json",
+        )
+        .unwrap();
+        let offline = WynnClient::builder()
+            .base_url(server.url())
+            .store(FileStore::new(&dir).unwrap())
+            .offline(true)
+            .build()
+            .unwrap();
+        assert!(matches!(
+            offline.guild_list().await,
+            Err(WynnApiError::StoreError(_))
+        ));
+
+        // the store can't be written anymore, the request still succeeds
+        fs::remove_dir_all(&dir).unwrap();
+        assert!(client.guild_list().await.unwrap().is_empty());
+    }
+
+    #[cfg(feature = "sqlite")]
+    #[test]
+    fn sqlite_store() {
+        check_store(&SqliteStore::in_memory().unwrap());
+    }
+
+    #[tokio::test]
+    async fn offline_mode() {
+        let dir = temp_dir("offline_mode");
+        let server = MockServer::start(|_| MockResponse::json("{}")).await;
+        let client = WynnClient::builder()
+            .base_url(server.url())
+            .store(FileStore::new(&dir).unwrap())
+            .build()
+            .unwrap();
+        client.guild_list().await.unwrap();
+        assert_eq!(server.request_count(), 1);
+
+        // a new client, like after a restart
+        let offline = WynnClient::builder()
+            .base_url(server.url())
+            .store(FileStore::new(&dir).unwrap())
+            .offline(true)
+            .build()
+            .unwrap();
+        assert!(offline.guild_list().await.unwrap().is_empty());
+        assert!(matches!(
+            offline.guild_teritories().await,
+            Err(WynnApiError::OfflineMiss(_))
+        ));
+        assert_eq!(server.request_count(), 1);
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,417 @@
+//! Rendering of api data to svg images.
+//!
+//! The output only depends on the input, not on the order of a `HashMap`, so it can be compared with a saved image.
+
+use crate::{
+    geometry::Rect,
+    guild::{GuildDescription, Territory},
+    map::MarkerLocation,
+    uuid::GuildUuid,
+    Map,
+};
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Write},
+};
+
+/// space around the map in pixels
+const PADDING: f64 = 10.0;
+const LEGEND_WIDTH: f64 = 220.0;
+const LEGEND_ROW: f64 = 18.0;
+
+/// Escapes text for use in svg text and attributes.
+pub(crate) fn escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Formats a number with at most 2 decimals, without trailing zeros.
+pub(crate) struct Num(pub f64);
+
+impl fmt::Display for Num {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let rounded = (self.0 * 100.0).round() / 100.0;
+        // avoids printing `-0`
+        let s = format!("{:.2}", rounded + 0.0);
+        f.write_str(s.trim_end_matches('0').trim_end_matches('.'))
+    }
+}
+
+/// Converts minecraft coordinates to pixels: `pixel = (block - origin) * scale`.
+///
+/// North is up, like on the map of the game.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct MapTransform {
+    /// pixels per block
+    pub scale: f64,
+    /// the block coordinates that end up at pixel `(0, 0)`
+    pub origin: (f64, f64),
+}
+
+impl MapTransform {
+    pub fn new(scale: f64, origin: (f64, f64)) -> Self {
+        Self { scale, origin }
+    }
+
+    /// Scales `bounds` to be `width` pixels wide, with its top left corner at pixel `(0, 0)`.
+    pub fn fit(bounds: Rect, width: f64) -> Self {
+        Self {
+            scale: width / bounds.width().max(1) as f64,
+            origin: (bounds.min_x as f64, bounds.min_z as f64),
+        }
+    }
+
+    pub fn apply(&self, x: f64, z: f64) -> (f64, f64) {
+        (
+            (x - self.origin.0) * self.scale,
+            (z - self.origin.1) * self.scale,
+        )
+    }
+}
+
+/// The colour of a guild on a [`TerritoryMap`], derived from its uuid so a guild keeps its colour between maps.
+pub fn guild_colour(guild: GuildUuid) -> String {
+    let hash = guild.as_u128();
+    let hue = hash % 360;
+    let lightness = 45 + (hash / 360 % 3) * 10;
+    format!("hsl({hue}, 65%, {lightness}%)")
+}
+
+/// Renders the owners of territories, as returned by [`guild_teritories`](crate::WynnClient::guild_teritories),
+/// to an svg.
+///
+/// ```no_run
+/// # async fn run() -> Result<(), wynncraft_api::WynnApiError> {
+/// use wynncraft_api::{svg::TerritoryMap, WynnClient};
+///
+/// let client = WynnClient::new();
+/// let territories = client.guild_teritories().await?;
+/// let markers = client.marker_locations().await?;
+/// let svg = TerritoryMap::new(&territories)
+///     .legend(true)
+///     .markers(&markers)
+///     .render();
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct TerritoryMap<'a> {
+    territories: &'a Map<String, Territory>,
+    transform: Option<MapTransform>,
+    legend: bool,
+    labels: bool,
+    markers: &'a [MarkerLocation],
+    marker_icons: Option<String>,
+    colours: BTreeMap<GuildUuid, String>,
+}
+
+impl<'a> TerritoryMap<'a> {
+    pub fn new(territories: &'a Map<String, Territory>) -> Self {
+        Self {
+            territories,
+            transform: None,
+            legend: false,
+            labels: true,
+            markers: &[],
+            marker_icons: None,
+            colours: BTreeMap::new(),
+        }
+    }
+
+    /// How to convert coordinates to pixels, by default the map is fit to 800 pixels wide.
+    pub fn transform(mut self, transform: MapTransform) -> Self {
+        self.transform = Some(transform);
+        self
+    }
+
+    /// Adds a list of the guilds with their colour and amount of territories next to the map.
+    pub fn legend(mut self, legend: bool) -> Self {
+        self.legend = legend;
+        self
+    }
+
+    /// Whether to write the prefix of the owner on every territory, on by default.
+    pub fn labels(mut self, labels: bool) -> Self {
+        self.labels = labels;
+        self
+    }
+
+    /// Draws `markers` over the territories, as dots unless [`marker_icons`](Self::marker_icons) is set.
+    pub fn markers(mut self, markers: &'a [MarkerLocation]) -> Self {
+        self.markers = markers;
+        self
+    }
+
+    /// Draws markers as images, the url of an icon is `base_url` followed by [`MarkerLocation::icon`].
+    pub fn marker_icons(mut self, base_url: impl Into<String>) -> Self {
+        self.marker_icons = Some(base_url.into());
+        self
+    }
+
+    /// Uses `colour` (any svg colour) for `guild` instead of [`guild_colour`].
+    pub fn colour(mut self, guild: GuildUuid, colour: impl Into<String>) -> Self {
+        self.colours.insert(guild, colour.into());
+        self
+    }
+
+    fn colour_of(&self, guild: GuildUuid) -> String {
+        self.colours
+            .get(&guild)
+            .cloned()
+            .unwrap_or_else(|| guild_colour(guild))
+    }
+
+    /// The guilds on the map with their amount of territories, the biggest first.
+    fn guilds(&self) -> Vec<(&GuildDescription, usize)> {
+        let mut guilds: BTreeMap<GuildUuid, (&GuildDescription, usize)> = BTreeMap::new();
+        for territory in self.territories.values() {
+            guilds
+                .entry(territory.guild.uuid)
+                .or_insert((&territory.guild, 0))
+                .1 += 1;
+        }
+        let mut guilds: Vec<_> = guilds.into_values().collect();
+        guilds.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.name.cmp(&b.0.name)));
+        guilds
+    }
+
+    pub fn render(&self) -> String {
+        // sorted so the output doesn't depend on the order of the map
+        let territories: BTreeMap<_, _> = self.territories.iter().collect();
+        let bounds = territories
+            .values()
+            .map(|t| t.location.rect())
+            .chain(
+                self.markers
+                    .iter()
+                    .map(|m| Rect::new((m.x, m.z), (m.x, m.z))),
+            )
+            .reduce(|a, b| {
+                Rect::new(
+                    (a.min_x.min(b.min_x), a.min_z.min(b.min_z)),
+                    (a.max_x.max(b.max_x), a.max_z.max(b.max_z)),
+                )
+            })
+            .unwrap_or(Rect::new((0, 0), (0, 0)));
+        let transform = self
+            .transform
+            .unwrap_or_else(|| MapTransform::fit(bounds, 800.0));
+        let (min, max) = (
+            transform.apply(bounds.min_x as f64, bounds.min_z as f64),
+            transform.apply(bounds.max_x as f64, bounds.max_z as f64),
+        );
+        let guilds = self.guilds();
+
+        let map_width = max.0 - min.0 + 2.0 * PADDING;
+        let mut width = map_width;
+        let mut height = max.1 - min.1 + 2.0 * PADDING;
+        if self.legend {
+            width += LEGEND_WIDTH;
+            height = height.max(2.0 * PADDING + LEGEND_ROW * guilds.len() as f64);
+        }
+        // moves the top left corner of the map to the padding
+        let shift = (PADDING - min.0, PADDING - min.1);
+        let point = |x: f64, z: f64| {
+            let (x, y) = transform.apply(x, z);
+            (x + shift.0, y + shift.1)
+        };
+
+        let mut svg = String::new();
+        // writing to a string can't fail
+        let _ = writeln!(
+            svg,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{}" height="{}" viewBox="0 0 {} {}">"#,
+            Num(width),
+            Num(height),
+            Num(width),
+            Num(height)
+        );
+        let _ = writeln!(
+            svg,
+            r##"<g class="territories" stroke="#000" stroke-width="1">"##
+        );
+        for (name, territory) in &territories {
+            let rect = territory.location.rect();
+            let (x1, y1) = point(rect.min_x as f64, rect.min_z as f64);
+            let (x2, y2) = point(rect.max_x as f64, rect.max_z as f64);
+            let _ = writeln!(
+                svg,
+                r#"<rect x="{}" y="{}" width="{}" height="{}" fill="{}" fill-opacity="0.6"><title>{} ({})</title></rect>"#,
+                Num(x1),
+                Num(y1),
+                Num(x2 - x1),
+                Num(y2 - y1),
+                escape(&self.colour_of(territory.guild.uuid)),
+                escape(name),
+                escape(&territory.guild.name)
+            );
+        }
+        let _ = writeln!(svg, "</g>");
+
+        if self.labels {
+            let _ = writeln!(
+                svg,
+                r#"<g class="labels" font-family="sans-serif" text-anchor="middle" dominant-baseline="central">"#
+            );
+            for territory in territories.values() {
+                let rect = territory.location.rect();
+                let (x, y) = rect.centre();
+                let (x, y) = point(x, y);
+                // small enough to fit a 4 letter prefix
+                let size = (rect.width().min(rect.depth()) as f64 * transform.scale / 3.0)
+                    .clamp(4.0, 14.0);
+                let _ = writeln!(
+                    svg,
+                    r#"<text x="{}" y="{}" font-size="{}">{}</text>"#,
+                    Num(x),
+                    Num(y),
+                    Num(size),
+                    escape(&territory.guild.prefix)
+                );
+            }
+            let _ = writeln!(svg, "</g>");
+        }
+
+        if !self.markers.is_empty() {
+            let _ = writeln!(svg, r#"<g class="markers">"#);
+            let mut markers: Vec<_> = self.markers.iter().collect();
+            markers.sort_by(|a, b| (&a.name, a.x, a.z).cmp(&(&b.name, b.x, b.z)));
+            for marker in markers {
+                let (x, y) = point(marker.x as f64, marker.z as f64);
+                match &self.marker_icons {
+                    Some(base_url) => {
+                        let _ = writeln!(
+                            svg,
+                            r#"<image href="{}" x="{}" y="{}" width="12" height="12"><title>{}</title></image>"#,
+                            escape(&format!("{base_url}{}", marker.icon)),
+                            Num(x - 6.0),
+                            Num(y - 6.0),
+                            escape(&marker.name)
+                        );
+                    }
+                    None => {
+                        let _ = writeln!(
+                            svg,
+                            r##"<circle cx="{}" cy="{}" r="3" fill="#fff" stroke="#000"><title>{}</title></circle>"##,
+                            Num(x),
+                            Num(y),
+                            escape(&marker.name)
+                        );
+                    }
+                }
+            }
+            let _ = writeln!(svg, "</g>");
+        }
+
+        if self.legend {
+            let _ = writeln!(
+                svg,
+                r#"<g class="legend" font-family="sans-serif" font-size="12">"#
+            );
+            for (i, (guild, count)) in guilds.iter().enumerate() {
+                let y = PADDING + LEGEND_ROW * i as f64;
+                let _ = writeln!(
+                    svg,
+                    r#"<rect x="{}" y="{}" width="12" height="12" fill="{}"/><text x="{}" y="{}">[{}] {} ({count})</text>"#,
+                    Num(map_width),
+                    Num(y),
+                    escape(&self.colour_of(guild.uuid)),
+                    Num(map_width + 18.0),
+                    Num(y + 10.0),
+                    escape(&guild.prefix),
+                    escape(&guild.name)
+                );
+            }
+            let _ = writeln!(svg, "</g>");
+        }
+        svg.push_str("</svg>\n");
+        svg
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `(name, guild, [start x, start z, end x, end z])`
+    fn territories(owners: &[(&str, u128, [i64; 4])]) -> Map<String, Territory> {
+        owners
+            .iter()
+            .map(|&(name, guild, [x1, z1, x2, z2])| {
+                let json = serde_json::json!({
+                    "guild": {"uuid": GuildUuid::from_u128(guild), "name": format!("Guild <{guild}>"), "prefix": format!("G{guild}")},
+                    "acquired": "2024-09-01T00:00:00.000Z",
+                    "location": {"start": [x1, z1], "end": [x2, z2]}
+                });
+                (name.to_owned(), serde_json::from_value(json).unwrap())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn numbers() {
+        assert_eq!(Num(1.0).to_string(), "1");
+        assert_eq!(Num(1.5).to_string(), "1.5");
+        assert_eq!(Num(1.005).to_string(), "1");
+        assert_eq!(Num(2.0 / 3.0).to_string(), "0.67");
+        assert_eq!(Num(-0.001).to_string(), "0");
+        assert_eq!(escape(r#"<a & "b">"#), "&lt;a &amp; &quot;b&quot;&gt;");
+    }
+
+    #[test]
+    fn territory_map() {
+        let territories = territories(&[
+            ("Ragni", 1, [100, 0, 0, 100]),
+            ("Detlas", 2, [100, 0, 200, 50]),
+            ("Nemract", 1, [100, 50, 200, 100]),
+        ]);
+        let markers: Vec<MarkerLocation> = serde_json::from_str(
+            r#"[{"name": "Ragni Bank", "icon": "Content_Bank.png", "x": "50", "y": "64", "z": "25"}]"#,
+        )
+        .unwrap();
+        let svg = TerritoryMap::new(&territories)
+            .transform(MapTransform::new(0.5, (0.0, 0.0)))
+            .colour(GuildUuid::from_u128(2), "red")
+            .legend(true)
+            .markers(&markers)
+            .render();
+        let expected = r##"<svg xmlns="http://www.w3.org/2000/svg" width="340" height="70" viewBox="0 0 340 70">
+<g class="territories" stroke="#000" stroke-width="1">
+<rect x="60" y="10" width="50" height="25" fill="red" fill-opacity="0.6"><title>Detlas (Guild &lt;2&gt;)</title></rect>
+<rect x="60" y="35" width="50" height="25" fill="hsl(1, 65%, 45%)" fill-opacity="0.6"><title>Nemract (Guild &lt;1&gt;)</title></rect>
+<rect x="10" y="10" width="50" height="50" fill="hsl(1, 65%, 45%)" fill-opacity="0.6"><title>Ragni (Guild &lt;1&gt;)</title></rect>
+</g>
+<g class="labels" font-family="sans-serif" text-anchor="middle" dominant-baseline="central">
+<text x="85" y="22.5" font-size="8.33">G2</text>
+<text x="85" y="47.5" font-size="8.33">G1</text>
+<text x="35" y="35" font-size="14">G1</text>
+</g>
+<g class="markers">
+<circle cx="35" cy="22.5" r="3" fill="#fff" stroke="#000"><title>Ragni Bank</title></circle>
+</g>
+<g class="legend" font-family="sans-serif" font-size="12">
+<rect x="120" y="10" width="12" height="12" fill="hsl(1, 65%, 45%)"/><text x="138" y="20">[G1] Guild &lt;1&gt; (2)</text>
+<rect x="120" y="28" width="12" height="12" fill="red"/><text x="138" y="38">[G2] Guild &lt;2&gt; (1)</text>
+</g>
+</svg>
+"##;
+        assert_eq!(svg, expected);
+
+        // the default transform fits the map to 800 pixels
+        let svg = TerritoryMap::new(&territories).labels(false).render();
+        assert!(
+            svg.starts_with(r#"<svg xmlns="http://www.w3.org/2000/svg" width="820" height="420""#)
+        );
+        assert!(!svg.contains("<text"));
+    }
+}
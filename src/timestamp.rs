@@ -0,0 +1,109 @@
+//! Points in time sent by the api, like the date a player first joined.
+//!
+//! Dates are kept as the string the api sent in every build, in the format `2024-09-01T18:30:00.000Z`.
+//! [`to_system_time`] turns them into a `SystemTime`, with the `chrono` feature types with a date also have
+//! accessors that return a [`chrono::DateTime<Utc>`](chrono::DateTime), like [`PlayerStats::last_join_at`](crate::player::PlayerStats::last_join_at).
+
+/// the format the api uses for almost all of its dates
+#[cfg(feature = "chrono")]
+pub const API_FORMAT: &str = "%Y-%m-%dT%H:%M:%S%.3fZ";
+
+/// A date of the api that couldn't be parsed.
+#[cfg(feature = "chrono")]
+#[derive(Debug, PartialEq, Eq, Clone, thiserror::Error)]
+#[error("`{0}` is not a valid date")]
+pub struct InvalidTimestamp(pub String);
+
+/// Parses a date in the format of the api, some endpoints leave out the `T` and timezone, these are in utc.
+#[cfg(feature = "chrono")]
+pub fn parse(s: &str) -> Result<chrono::DateTime<chrono::Utc>, InvalidTimestamp> {
+    use chrono::{DateTime, NaiveDateTime, Utc};
+
+    if let Ok(time) = DateTime::parse_from_rfc3339(s) {
+        return Ok(time.with_timezone(&Utc));
+    }
+    NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S%.f")
+        .map(|time| time.and_utc())
+        .map_err(|_| InvalidTimestamp(s.to_owned()))
+}
+
+/// Formats a date the way the api does.
+#[cfg(feature = "chrono")]
+pub fn format(time: &chrono::DateTime<chrono::Utc>) -> String {
+    time.format(API_FORMAT).to_string()
+}
+
+/// A date of the api as a `SystemTime`, `None` if it is before 1970 or not a valid date.
+pub fn to_system_time(time: &str) -> Option<std::time::SystemTime> {
+    // `2024-09-01T18:30:00.000Z`, the `T` may also be a space and the fraction and `Z` are optional
+    let time = time.trim().trim_end_matches('Z');
+    let (date, time) = time.split_once(['T', ' '])?;
+    let mut date = date.splitn(3, '-').map(str::parse::<i64>);
+    let (year, month, day) = (date.next()?.ok()?, date.next()?.ok()?, date.next()?.ok()?);
+    let (time, fraction) = time.split_once('.').unwrap_or((time, ""));
+    let mut time = time.splitn(3, ':').map(str::parse::<u64>);
+    let (hour, minute, second) = (time.next()?.ok()?, time.next()?.ok()?, time.next()?.ok()?);
+    if !(1..=12).contains(&month)
+        || !(1..=31).contains(&day)
+        || hour > 23
+        || minute > 59
+        || second > 60
+    {
+        return None;
+    }
+    let nanos = if fraction.is_empty() {
+        0
+    } else {
+        let digits: String = fraction
+            .chars()
+            .chain(std::iter::repeat('0'))
+            .take(9)
+            .collect();
+        digits.parse().ok()?
+    };
+
+    // days since 1970-01-01 in the proleptic gregorian calendar
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year.rem_euclid(400);
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    let days = u64::try_from(era * 146097 + day_of_era - 719468).ok()?;
+
+    let secs = days * 86400 + hour * 3600 + minute * 60 + second;
+    Some(std::time::UNIX_EPOCH + std::time::Duration::new(secs, nanos))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    #[test]
+    fn system_time() {
+        assert_eq!(
+            to_system_time("2024-09-01T18:30:00.500Z"),
+            Some(UNIX_EPOCH + Duration::from_millis(1_725_215_400_500))
+        );
+        assert_eq!(
+            to_system_time("2024-09-01 18:30:00"),
+            Some(UNIX_EPOCH + Duration::from_secs(1_725_215_400))
+        );
+        assert_eq!(to_system_time("yesterday"), None);
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn api_format() {
+        use chrono::{TimeZone, Utc};
+
+        let time = parse("2024-09-01T18:30:00.000Z").unwrap();
+        assert_eq!(time, Utc.with_ymd_and_hms(2024, 9, 1, 18, 30, 0).unwrap());
+        assert_eq!(format(&time), "2024-09-01T18:30:00.000Z");
+        assert_eq!(parse("2024-09-01 18:30:00"), Ok(time));
+        assert_eq!(
+            parse("yesterday"),
+            Err(InvalidTimestamp("yesterday".to_owned()))
+        );
+    }
+}
@@ -0,0 +1,201 @@
+use serde::{de::Error as _, Deserialize, Deserializer, Serialize, Serializer};
+use std::{
+    fmt::{self, Display},
+    str::FromStr,
+};
+use thiserror::Error;
+
+#[derive(Debug, PartialEq, Eq, Clone, Error)]
+#[error("`{0}` is not a valid uuid")]
+pub struct ParseUuidError(pub String);
+
+/// parses both `xxxxxxxx-xxxx-xxxx-xxxx-xxxxxxxxxxxx` and `xxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxx`
+fn parse_uuid(s: &str) -> Result<u128, ParseUuidError> {
+    let error = || ParseUuidError(s.to_owned());
+    let bytes = s.as_bytes();
+    let hex: String = match bytes.len() {
+        32 => s.to_owned(),
+        36 if [8, 13, 18, 23].iter().all(|&i| bytes[i] == b'-') => s.replace('-', ""),
+        _ => return Err(error()),
+    };
+    if hex.len() != 32 || !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return Err(error());
+    }
+    u128::from_str_radix(&hex, 16).map_err(|_| error())
+}
+
+fn fmt_uuid(uuid: u128, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    let hex = format!("{uuid:032x}");
+    write!(
+        f,
+        "{}-{}-{}-{}-{}",
+        &hex[..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..]
+    )
+}
+
+macro_rules! uuid_type {
+    ($(#[$meta:meta])* $name:ident) => {
+        $(#[$meta])*
+        ///
+        /// Parsed from both the dashed and undashed form, always displayed and serialized dashed.
+        #[derive(Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Clone, Copy)]
+        pub struct $name(u128);
+
+        impl $name {
+            pub const fn from_u128(uuid: u128) -> Self {
+                Self(uuid)
+            }
+
+            pub const fn as_u128(&self) -> u128 {
+                self.0
+            }
+
+            /// The uuid without dashes.
+            pub fn simple(&self) -> String {
+                format!("{:032x}", self.0)
+            }
+        }
+
+        impl FromStr for $name {
+            type Err = ParseUuidError;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                parse_uuid(s).map(Self)
+            }
+        }
+
+        impl Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                fmt_uuid(self.0, f)
+            }
+        }
+
+        impl Serialize for $name {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.collect_str(self)
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $name {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                let s = String::deserialize(deserializer)?;
+                s.parse().map_err(D::Error::custom)
+            }
+        }
+    };
+}
+
+uuid_type!(
+    /// The uuid of a minecraft account.
+    PlayerUuid
+);
+uuid_type!(
+    /// The uuid of a single character of a player.
+    CharacterUuid
+);
+uuid_type!(
+    /// The uuid of a guild.
+    GuildUuid
+);
+
+/// A player, either by username or by uuid.
+///
+/// Converting a string into a `PlayerRef` picks [`PlayerRef::Uuid`] if it is a valid uuid,
+/// usernames are never long enough to be mistaken for one.
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+pub enum PlayerRef {
+    Username(String),
+    Uuid(PlayerUuid),
+}
+
+impl Display for PlayerRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PlayerRef::Username(name) => f.write_str(name),
+            PlayerRef::Uuid(uuid) => uuid.fmt(f),
+        }
+    }
+}
+
+impl From<&str> for PlayerRef {
+    fn from(value: &str) -> Self {
+        match value.parse() {
+            Ok(uuid) => PlayerRef::Uuid(uuid),
+            Err(_) => PlayerRef::Username(value.to_owned()),
+        }
+    }
+}
+
+impl From<&String> for PlayerRef {
+    fn from(value: &String) -> Self {
+        value.as_str().into()
+    }
+}
+
+impl From<String> for PlayerRef {
+    fn from(value: String) -> Self {
+        value.as_str().into()
+    }
+}
+
+impl From<PlayerUuid> for PlayerRef {
+    fn from(value: PlayerUuid) -> Self {
+        PlayerRef::Uuid(value)
+    }
+}
+
+impl From<&PlayerUuid> for PlayerRef {
+    fn from(value: &PlayerUuid) -> Self {
+        PlayerRef::Uuid(*value)
+    }
+}
+
+impl From<&PlayerRef> for PlayerRef {
+    fn from(value: &PlayerRef) -> Self {
+        value.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_uuids() {
+        let dashed: PlayerUuid = "55a92635-0482-4e0a-b4ff-8284e7c8a326".parse().unwrap();
+        let simple: PlayerUuid = "55A9263504824E0AB4FF8284E7C8A326".parse().unwrap();
+        assert_eq!(dashed, simple);
+        assert_eq!(dashed.to_string(), "55a92635-0482-4e0a-b4ff-8284e7c8a326");
+        assert_eq!(dashed.simple(), "55a9263504824e0ab4ff8284e7c8a326");
+
+        assert!("55a92635-0482-4e0a-b4ff-8284e7c8a32"
+            .parse::<GuildUuid>()
+            .is_err());
+        assert!("55a926350-482-4e0a-b4ff-8284e7c8a326"
+            .parse::<GuildUuid>()
+            .is_err());
+        assert!("55a92635-0482-4e0a-b4ff-8284e7c8a32g"
+            .parse::<GuildUuid>()
+            .is_err());
+
+        let json = serde_json::to_string(&dashed).unwrap();
+        assert_eq!(json, r#""55a92635-0482-4e0a-b4ff-8284e7c8a326""#);
+        assert_eq!(serde_json::from_str::<PlayerUuid>(&json).unwrap(), dashed);
+        assert!(serde_json::from_str::<CharacterUuid>(r#""de_grote""#).is_err());
+    }
+
+    #[test]
+    fn player_refs() {
+        assert_eq!(
+            PlayerRef::from("de_grote"),
+            PlayerRef::Username("de_grote".to_owned())
+        );
+        let uuid = PlayerRef::from("55a9263504824e0ab4ff8284e7c8a326");
+        assert!(matches!(uuid, PlayerRef::Uuid(_)));
+        assert_eq!(uuid.to_string(), "55a92635-0482-4e0a-b4ff-8284e7c8a326");
+    }
+}
@@ -0,0 +1,356 @@
+//! Streams of changes, made by polling an endpoint and comparing every response with the previous one.
+//!
+//! The watchers use the normal requests of the [`WynnClient`], so they go through its cache and rate limiter.
+//! A response that is still cached is not requested again, so polling faster than the api updates doesn't
+//! cost extra requests.
+
+use crate::{
+    guild::{GuildDescription, GuildDiff, GuildEvent, Territory},
+    news::NewsArticle,
+    player::OnlinePlayerList,
+    Identifier, Map, World, WynnApiError, WynnClient,
+};
+use futures::{
+    stream::{self, BoxStream},
+    Future, StreamExt,
+};
+use serde::{Deserialize, Serialize};
+use std::{collections::VecDeque, time::Duration};
+use tokio::time::MissedTickBehavior;
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum PlayerEvent {
+    PlayerOnline {
+        player: String,
+        world: World,
+    },
+    PlayerOffline {
+        player: String,
+        world: World,
+    },
+    WorldChanged {
+        player: String,
+        from: World,
+        to: World,
+    },
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum TerritoryEvent {
+    TerritoryCaptured {
+        territory: String,
+        from: GuildDescription,
+        to: GuildDescription,
+    },
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum NewsEvent {
+    NewArticle(NewsArticle),
+}
+
+/// Polls `fetch` every `interval` and yields the events `diff` finds between two consecutive responses.
+///
+/// The first response is only used to compare the next one with, so it doesn't yield any events.
+/// When a request fails the error is yielded and the watcher keeps polling,
+/// the next successful response is compared with the last successful one.
+pub fn watch_with<T, E, F, Fut, D>(
+    interval: Duration,
+    fetch: F,
+    diff: D,
+) -> BoxStream<'static, Result<E, WynnApiError>>
+where
+    T: Send + 'static,
+    E: Send + 'static,
+    F: FnMut() -> Fut + Send + 'static,
+    Fut: Future<Output = Result<T, WynnApiError>> + Send,
+    D: Fn(&T, &T) -> Vec<E> + Send + 'static,
+{
+    struct State<T, E, F, D> {
+        interval: tokio::time::Interval,
+        fetch: F,
+        diff: D,
+        previous: Option<T>,
+        pending: VecDeque<E>,
+    }
+
+    let mut interval = tokio::time::interval(interval);
+    interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    let state = State {
+        interval,
+        fetch,
+        diff,
+        previous: None,
+        pending: VecDeque::new(),
+    };
+    stream::unfold(state, |mut state| async move {
+        loop {
+            if let Some(event) = state.pending.pop_front() {
+                return Some((Ok(event), state));
+            }
+            state.interval.tick().await;
+            match (state.fetch)().await {
+                Ok(next) => {
+                    if let Some(previous) = &state.previous {
+                        state.pending.extend((state.diff)(previous, &next));
+                    }
+                    state.previous = Some(next);
+                }
+                Err(e) => return Some((Err(e), state)),
+            }
+        }
+    })
+    .boxed()
+}
+
+pub fn diff_online_players(old: &OnlinePlayerList, new: &OnlinePlayerList) -> Vec<PlayerEvent> {
+    let mut events = Vec::new();
+    for (player, &world) in &new.players {
+        match old.players.get(player) {
+            None => events.push(PlayerEvent::PlayerOnline {
+                player: player.clone(),
+                world,
+            }),
+            Some(&from) if from != world => events.push(PlayerEvent::WorldChanged {
+                player: player.clone(),
+                from,
+                to: world,
+            }),
+            Some(_) => {}
+        }
+    }
+    for (player, &world) in &old.players {
+        if !new.players.contains_key(player) {
+            events.push(PlayerEvent::PlayerOffline {
+                player: player.clone(),
+                world,
+            });
+        }
+    }
+    events
+}
+
+pub fn diff_territories(
+    old: &Map<String, Territory>,
+    new: &Map<String, Territory>,
+) -> Vec<TerritoryEvent> {
+    new.iter()
+        .filter_map(|(name, territory)| {
+            let previous = old.get(name)?;
+            (previous.guild.uuid != territory.guild.uuid).then(|| {
+                TerritoryEvent::TerritoryCaptured {
+                    territory: name.clone(),
+                    from: previous.guild.clone(),
+                    to: territory.guild.clone(),
+                }
+            })
+        })
+        .collect()
+}
+
+/// Articles are identified by their forum thread, new articles are yielded from old to new.
+pub fn diff_news(old: &[NewsArticle], new: &[NewsArticle]) -> Vec<NewsEvent> {
+    new.iter()
+        .rev()
+        .filter(|article| old.iter().all(|o| o.forum_thread != article.forum_thread))
+        .map(|article| NewsEvent::NewArticle(article.clone()))
+        .collect()
+}
+
+impl WynnClient {
+    /// Yields players that log on, log off or switch worlds, keyed by username or uuid depending on `identifier`.
+    pub fn watch_online_players(
+        &self,
+        identifier: Identifier,
+        interval: Duration,
+    ) -> BoxStream<'static, Result<PlayerEvent, WynnApiError>> {
+        let client = self.clone();
+        watch_with(
+            interval,
+            move || {
+                let client = client.clone();
+                async move { client.online_player_list(identifier, &[]).await }
+            },
+            diff_online_players,
+        )
+    }
+
+    pub fn watch_territories(
+        &self,
+        interval: Duration,
+    ) -> BoxStream<'static, Result<TerritoryEvent, WynnApiError>> {
+        let client = self.clone();
+        watch_with(
+            interval,
+            move || {
+                let client = client.clone();
+                async move { client.guild_teritories().await }
+            },
+            diff_territories,
+        )
+    }
+
+    /// Yields the [`GuildEvent`]s of the guild, see [`GuildDiff`].
+    pub fn watch_guild(
+        &self,
+        guild_name: &str,
+        identifier: Identifier,
+        interval: Duration,
+    ) -> BoxStream<'static, Result<GuildEvent, WynnApiError>> {
+        let client = self.clone();
+        let guild_name = guild_name.to_owned();
+        watch_with(
+            interval,
+            move || {
+                let client = client.clone();
+                let guild_name = guild_name.clone();
+                async move { client.guild_by_name(&guild_name, identifier).await }
+            },
+            |old, new| GuildDiff::between(old, new).events,
+        )
+    }
+
+    pub fn watch_news(
+        &self,
+        interval: Duration,
+    ) -> BoxStream<'static, Result<NewsEvent, WynnApiError>> {
+        let client = self.clone();
+        watch_with(
+            interval,
+            move || {
+                let client = client.clone();
+                async move { client.latest_news().await }
+            },
+            |old: &Vec<NewsArticle>, new: &Vec<NewsArticle>| diff_news(old, new),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::{MockResponse, MockServer};
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    };
+    use tokio::time::Instant;
+
+    fn players(players: &[(&str, u8)]) -> OnlinePlayerList {
+        OnlinePlayerList {
+            total: players.len() as u64,
+            players: players
+                .iter()
+                .map(|&(name, world)| (name.to_owned(), World(world)))
+                .collect(),
+        }
+    }
+
+    /// the order of events within one diff depends on the map type
+    async fn next_events(
+        events: &mut BoxStream<'static, Result<PlayerEvent, WynnApiError>>,
+        n: usize,
+    ) -> Vec<PlayerEvent> {
+        let mut next = Vec::new();
+        for _ in 0..n {
+            next.push(events.next().await.unwrap().unwrap());
+        }
+        next.sort_by_key(|event| format!("{event:?}"));
+        next
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn fake_clock() {
+        // a stubbed transport that returns the next snapshot every time
+        let snapshots = Arc::new(Mutex::new(VecDeque::from([
+            Ok(players(&[("Alice", 1), ("Bob", 2)])),
+            Err(WynnApiError::OfflineMiss("/player".to_owned())),
+            Ok(players(&[("Alice", 3), ("Carol", 1)])),
+        ])));
+        let start = Instant::now();
+        let mut events = watch_with(
+            Duration::from_secs(60),
+            move || {
+                let next = snapshots.lock().unwrap().pop_front();
+                async move { next.unwrap_or_else(|| Ok(players(&[]))) }
+            },
+            diff_online_players,
+        );
+
+        assert!(matches!(
+            events.next().await,
+            Some(Err(WynnApiError::OfflineMiss(_)))
+        ));
+        assert_eq!(start.elapsed(), Duration::from_secs(60));
+        assert_eq!(
+            next_events(&mut events, 3).await,
+            [
+                PlayerEvent::PlayerOffline {
+                    player: "Bob".to_owned(),
+                    world: World(2)
+                },
+                PlayerEvent::PlayerOnline {
+                    player: "Carol".to_owned(),
+                    world: World(1)
+                },
+                PlayerEvent::WorldChanged {
+                    player: "Alice".to_owned(),
+                    from: World(1),
+                    to: World(3)
+                },
+            ]
+        );
+        assert_eq!(start.elapsed(), Duration::from_secs(120));
+        // everyone went offline
+        assert_eq!(
+            next_events(&mut events, 2).await,
+            [
+                PlayerEvent::PlayerOffline {
+                    player: "Alice".to_owned(),
+                    world: World(3)
+                },
+                PlayerEvent::PlayerOffline {
+                    player: "Carol".to_owned(),
+                    world: World(1)
+                },
+            ]
+        );
+        assert_eq!(start.elapsed(), Duration::from_secs(180));
+    }
+
+    #[tokio::test]
+    async fn watch_news() {
+        let requests = AtomicUsize::new(0);
+        let server = MockServer::start(move |_| {
+            let article = |n: usize| {
+                format!(
+                    r#"{{"title": "Update {n}", "date": "2024-09-0{n}T12:00:00.000Z", "forumThread": "https://forums.wynncraft.com/threads/{n}", "author": "Salted", "content": "", "comments": "0"}}"#
+                )
+            };
+            let body = match requests.fetch_add(1, Ordering::SeqCst) {
+                0 => format!("[{}]", article(1)),
+                _ => format!("[{}, {}, {}]", article(3), article(2), article(1)),
+            };
+            MockResponse::json(body)
+        })
+        .await;
+        let client = WynnClient::builder()
+            .base_url(server.url())
+            .build()
+            .unwrap();
+        let mut news = client.watch_news(Duration::from_millis(50));
+        let titles: Vec<String> = news
+            .by_ref()
+            .take(2)
+            .map(|event| match event.unwrap() {
+                NewsEvent::NewArticle(article) => article.title,
+            })
+            .collect()
+            .await;
+        assert_eq!(titles, ["Update 2", "Update 3"]);
+        assert_eq!(server.request_count(), 2);
+    }
+}